@@ -0,0 +1,365 @@
+// Interactive lineage explorer served at `/lineage/explorer`.
+//
+// The graph starts with a single node and grows lazily by fetching `/lineage/neighbors?node=${ID}`.
+(function () {
+  'use strict';
+
+  var SVG_NS = 'http://www.w3.org/2000/svg';
+  var NODE_WIDTH = 170;
+  var NODE_HEIGHT = 44;
+  var RANK_GAP = 240;
+  var ROW_GAP = 64;
+
+  var container = document.getElementById('lineage-explorer');
+  if (!container) {
+    return;
+  }
+
+  var state = {
+    nodes: new Map(), // ID => node
+    edges: new Map(), // key => edge
+    expanded: new Set(),
+    addedBy: new Map(), // ID => ID of the node whose expansion added it
+    root: container.dataset.node,
+    selected: null,
+    path: new Set(), // IDs of nodes and keys of edges on the highlighted path
+    view: { x: 20, y: 20, scale: 1 },
+  };
+
+  function el(tag, attrs, text) {
+    var e = tag === 'div' || tag === 'button' || tag === 'span' || tag === 'a'
+      ? document.createElement(tag)
+      : document.createElementNS(SVG_NS, tag);
+    Object.keys(attrs || {}).forEach(function (k) {
+      e.setAttribute(k, attrs[k]);
+    });
+    if (text !== undefined) {
+      e.textContent = text;
+    }
+    return e;
+  }
+
+  // Layout of the page.
+  var toolbar = el('div', { style: 'margin-bottom: 8px' });
+  var buttons = {
+    expand: el('button', {}, 'Expand'),
+    collapse: el('button', {}, 'Collapse'),
+    fit: el('button', {}, 'Fit'),
+    clearPath: el('button', {}, 'Clear path'),
+  };
+  Object.keys(buttons).forEach(function (k) {
+    toolbar.appendChild(buttons[k]);
+    toolbar.appendChild(document.createTextNode(' '));
+  });
+  var info = el('span', { style: 'margin-left: 12px' });
+  toolbar.appendChild(info);
+
+  var svg = el('svg', {
+    width: '100%',
+    height: '640',
+    style: 'border: 1px solid #d0d7de; cursor: grab; user-select: none',
+  });
+  var defs = el('defs');
+  var marker = el('marker', {
+    id: 'arrow', viewBox: '0 0 10 10', refX: '10', refY: '5',
+    markerWidth: '8', markerHeight: '8', orient: 'auto-start-reverse',
+  });
+  marker.appendChild(el('path', { d: 'M 0 0 L 10 5 L 0 10 z', fill: '#57606a' }));
+  defs.appendChild(marker);
+  svg.appendChild(defs);
+  var viewport = el('g');
+  svg.appendChild(viewport);
+
+  container.appendChild(toolbar);
+  container.appendChild(svg);
+
+  function edgeKey(edge) {
+    return edge.source + '>' + edge.target + '>' + edge.event.ty + '>' + JSON.stringify(edge.event.path);
+  }
+
+  function expand(id) {
+    if (state.expanded.has(id)) {
+      return Promise.resolve();
+    }
+    info.textContent = 'Loading ' + id + '...';
+    return fetch('/lineage/neighbors?node=' + encodeURIComponent(id))
+      .then(function (res) {
+        if (!res.ok) {
+          return res.text().then(function (text) { throw new Error(text); });
+        }
+        return res.json();
+      })
+      .then(function (graph) {
+        graph.nodes.forEach(function (node) {
+          if (!state.nodes.has(node.id) && node.id !== id) {
+            state.addedBy.set(node.id, id);
+          }
+          state.nodes.set(node.id, node);
+        });
+        graph.edges.forEach(function (edge) {
+          state.edges.set(edgeKey(edge), edge);
+        });
+        state.expanded.add(id);
+        render();
+      })
+      .catch(function (e) {
+        info.textContent = 'Failed to load ' + id + ': ' + e.message;
+      });
+  }
+
+  // Removes the nodes added by expanding `id` (recursively) except those on the way to the root.
+  function collapse(id) {
+    var removed = new Set();
+    var stack = [id];
+    while (stack.length > 0) {
+      var curr = stack.pop();
+      state.addedBy.forEach(function (parent, child) {
+        if (parent === curr && child !== state.root && !removed.has(child)) {
+          removed.add(child);
+          stack.push(child);
+        }
+      });
+    }
+    removed.forEach(function (child) {
+      state.nodes.delete(child);
+      state.expanded.delete(child);
+      state.addedBy.delete(child);
+      state.path.delete(child);
+    });
+    state.edges.forEach(function (edge, key) {
+      if (removed.has(edge.source) || removed.has(edge.target)) {
+        state.edges.delete(key);
+      }
+    });
+    state.expanded.delete(id);
+    render();
+  }
+
+  // Highlights the shortest path between `from` and `to` ignoring the directions of the edges.
+  function highlightPath(from, to) {
+    var prev = new Map([[from, null]]);
+    var queue = [from];
+    while (queue.length > 0 && !prev.has(to)) {
+      var curr = queue.shift();
+      state.edges.forEach(function (edge, key) {
+        var next = edge.source === curr ? edge.target : edge.target === curr ? edge.source : null;
+        if (next !== null && state.nodes.has(next) && !prev.has(next)) {
+          prev.set(next, { node: curr, edge: key });
+          queue.push(next);
+        }
+      });
+    }
+
+    state.path = new Set();
+    if (!prev.has(to)) {
+      info.textContent = 'No path between ' + from + ' and ' + to + ' in the explored graph.';
+      render();
+      return;
+    }
+    for (var id = to; id !== null;) {
+      state.path.add(id);
+      var step = prev.get(id);
+      if (step === null) {
+        break;
+      }
+      state.path.add(step.edge);
+      id = step.node;
+    }
+    render();
+  }
+
+  // Places the nodes in columns so that every edge goes from left to right.
+  function layout() {
+    var rank = new Map();
+    state.nodes.forEach(function (_, id) { rank.set(id, 0); });
+    for (var i = 0; i < state.nodes.size; i++) {
+      var changed = false;
+      state.edges.forEach(function (edge) {
+        if (rank.has(edge.source) && rank.has(edge.target) && rank.get(edge.target) <= rank.get(edge.source)) {
+          rank.set(edge.target, rank.get(edge.source) + 1);
+          changed = true;
+        }
+      });
+      if (!changed) {
+        break;
+      }
+    }
+
+    var columns = new Map();
+    state.nodes.forEach(function (_, id) {
+      var r = rank.get(id);
+      if (!columns.has(r)) {
+        columns.set(r, []);
+      }
+      columns.get(r).push(id);
+    });
+    var height = 0;
+    columns.forEach(function (ids) { height = Math.max(height, ids.length); });
+
+    var positions = new Map();
+    columns.forEach(function (ids, r) {
+      var offset = (height - ids.length) * ROW_GAP / 2;
+      ids.forEach(function (id, j) {
+        positions.set(id, { x: r * RANK_GAP, y: offset + j * ROW_GAP });
+      });
+    });
+    return positions;
+  }
+
+  function render() {
+    var positions = layout();
+    while (viewport.firstChild) {
+      viewport.removeChild(viewport.firstChild);
+    }
+
+    state.edges.forEach(function (edge, key) {
+      var s = positions.get(edge.source);
+      var t = positions.get(edge.target);
+      if (!s || !t) {
+        return;
+      }
+      var x1 = s.x + NODE_WIDTH;
+      var y1 = s.y + NODE_HEIGHT / 2;
+      var x2 = t.x;
+      var y2 = t.y + NODE_HEIGHT / 2;
+      var dx = Math.max(40, (x2 - x1) / 2);
+      var highlighted = state.path.has(key);
+      var path = el('path', {
+        d: 'M ' + x1 + ' ' + y1 + ' C ' + (x1 + dx) + ' ' + y1 + ', ' + (x2 - dx) + ' ' + y2 + ', ' + x2 + ' ' + y2,
+        fill: 'none',
+        stroke: highlighted ? '#fb8500' : '#8c959f',
+        'stroke-width': highlighted ? '3' : '1.5',
+        'marker-end': 'url(#arrow)',
+      });
+      path.appendChild(el('title', {}, edge.event.ty + ' ' + edge.event.path.join('.')));
+      viewport.appendChild(path);
+    });
+
+    state.nodes.forEach(function (node, id) {
+      var p = positions.get(id);
+      var isArtifact = id.charAt(0) === 'A';
+      var g = el('g', { transform: 'translate(' + p.x + ',' + p.y + ')', style: 'cursor: pointer' });
+      g.appendChild(el('rect', {
+        width: NODE_WIDTH,
+        height: NODE_HEIGHT,
+        rx: isArtifact ? '20' : '4',
+        fill: isArtifact ? '#ddf4ff' : '#fff8c5',
+        stroke: state.path.has(id) ? '#fb8500' : id === state.selected ? '#cf222e' : '#57606a',
+        'stroke-width': id === state.selected || state.path.has(id) ? '3' : state.expanded.has(id) ? '1' : '2',
+        'stroke-dasharray': state.expanded.has(id) ? '' : '4 2',
+      }));
+      g.appendChild(el('text', { x: '10', y: '18', 'font-size': '12', 'font-weight': 'bold' },
+        truncate(id + ' ' + node.type, 24)));
+      g.appendChild(el('text', { x: '10', y: '34', 'font-size': '11', fill: '#57606a' },
+        truncate(node.name || ('in=' + node.inputs + ', out=' + node.outputs), 26)));
+      g.appendChild(el('title', {}, id + ' ' + node.type + (node.name ? '\n' + node.name : '') +
+        '\nin=' + node.inputs + ', out=' + node.outputs +
+        '\n\nClick: select and expand\nShift+click: highlight the path from the selected node\nDouble-click: open the detail page'));
+      g.addEventListener('mousedown', function (e) { e.stopPropagation(); });
+      g.addEventListener('click', function (e) {
+        if (e.shiftKey && state.selected && state.selected !== id) {
+          highlightPath(state.selected, id);
+          return;
+        }
+        state.selected = id;
+        expand(id).then(render);
+        render();
+      });
+      g.addEventListener('dblclick', function () { window.open(node.url, '_blank'); });
+      viewport.appendChild(g);
+    });
+
+    applyView();
+    updateInfo();
+  }
+
+  function truncate(s, n) {
+    return s.length > n ? s.slice(0, n - 1) + '…' : s;
+  }
+
+  function updateInfo() {
+    while (info.firstChild) {
+      info.removeChild(info.firstChild);
+    }
+    var node = state.nodes.get(state.selected);
+    if (!node) {
+      info.textContent = state.nodes.size + ' nodes, ' + state.edges.size + ' edges';
+      return;
+    }
+    var link = el('a', { href: node.url }, state.selected + ' ' + node.type + (node.name ? ' ' + node.name : ''));
+    info.appendChild(link);
+    info.appendChild(document.createTextNode(
+      ' (in=' + node.inputs + ', out=' + node.outputs + ') | ' +
+      state.nodes.size + ' nodes, ' + state.edges.size + ' edges'));
+  }
+
+  function applyView() {
+    viewport.setAttribute('transform',
+      'translate(' + state.view.x + ',' + state.view.y + ') scale(' + state.view.scale + ')');
+  }
+
+  function fit() {
+    var box = viewport.getBBox();
+    if (box.width === 0 || box.height === 0) {
+      return;
+    }
+    var rect = svg.getBoundingClientRect();
+    var scale = Math.min(2, (rect.width - 40) / box.width, (rect.height - 40) / box.height);
+    state.view = {
+      x: (rect.width - box.width * scale) / 2 - box.x * scale,
+      y: (rect.height - box.height * scale) / 2 - box.y * scale,
+      scale: scale,
+    };
+    applyView();
+  }
+
+  // Panning and zooming.
+  var drag = null;
+  svg.addEventListener('mousedown', function (e) {
+    drag = { x: e.clientX - state.view.x, y: e.clientY - state.view.y };
+    svg.style.cursor = 'grabbing';
+  });
+  window.addEventListener('mousemove', function (e) {
+    if (drag) {
+      state.view.x = e.clientX - drag.x;
+      state.view.y = e.clientY - drag.y;
+      applyView();
+    }
+  });
+  window.addEventListener('mouseup', function () {
+    drag = null;
+    svg.style.cursor = 'grab';
+  });
+  svg.addEventListener('wheel', function (e) {
+    e.preventDefault();
+    var rect = svg.getBoundingClientRect();
+    var mx = e.clientX - rect.left;
+    var my = e.clientY - rect.top;
+    var factor = e.deltaY < 0 ? 1.1 : 1 / 1.1;
+    var scale = Math.min(4, Math.max(0.1, state.view.scale * factor));
+    factor = scale / state.view.scale;
+    state.view.x = mx - (mx - state.view.x) * factor;
+    state.view.y = my - (my - state.view.y) * factor;
+    state.view.scale = scale;
+    applyView();
+  }, { passive: false });
+
+  buttons.expand.addEventListener('click', function () {
+    if (state.selected) {
+      expand(state.selected);
+    }
+  });
+  buttons.collapse.addEventListener('click', function () {
+    if (state.selected) {
+      collapse(state.selected);
+    }
+  });
+  buttons.fit.addEventListener('click', fit);
+  buttons.clearPath.addEventListener('click', function () {
+    state.path = new Set();
+    render();
+  });
+
+  state.selected = state.root;
+  expand(state.root).then(fit);
+})();
@@ -1,4 +1,5 @@
 pub mod common;
+pub mod graph;
 pub mod hook;
 pub mod io;
 pub mod run;
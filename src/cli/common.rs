@@ -13,4 +13,11 @@ impl MetadataStoreOpt {
             .with_context(|| format!("cannot connect to the database: {:?}", self.database))?;
         Ok(store)
     }
+
+    /// Connects to the database directly (for queries that the `mlmd` crate lacks).
+    pub async fn connect_database(&self) -> anyhow::Result<sqlx::AnyConnection> {
+        crate::mlmd::connect(&self.database)
+            .await
+            .with_context(|| format!("cannot connect to the database: {:?}", self.database))
+    }
 }
@@ -0,0 +1,67 @@
+use crate::cli::common::MetadataStoreOpt;
+use crate::graph::{Graph, GraphFormat, GraphStyle, NodeId};
+use crate::hook::HookRunner;
+use anyhow::Context as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// Exports the lineage graph of an artifact or execution.
+#[derive(Debug, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct GraphOpt {
+    #[structopt(flatten)]
+    pub store: MetadataStoreOpt,
+
+    /// Node ID such as `A12` (artifact) or `E34` (execution).
+    pub node: NodeId,
+
+    #[structopt(long, default_value = "dot", possible_values = GraphFormat::POSSIBLE_VALUES)]
+    pub format: GraphFormat,
+
+    /// Hook configuration file used to show the summaries of the nodes (same as `weih run --hook`).
+    #[structopt(long)]
+    pub hook: Option<PathBuf>,
+
+    /// JSON file of the graph style (same as `weih run --graph-style`).
+    #[structopt(long)]
+    pub graph_style: Option<PathBuf>,
+
+    /// Output file (default: stdout).
+    #[structopt(long, short = "o")]
+    pub output: Option<PathBuf>,
+}
+
+impl GraphOpt {
+    pub async fn execute(&self) -> anyhow::Result<()> {
+        let hook_runner = HookRunner::load(self.hook.as_deref(), &self.store.database)?;
+        let style = self
+            .graph_style
+            .as_deref()
+            .map(GraphStyle::load)
+            .transpose()
+            .with_context(|| format!("cannot load the graph style: {:?}", self.graph_style))?
+            .unwrap_or_default();
+
+        let mut store = self.store.connect().await?;
+        let mut graph = Graph::new(&mut store, self.node).await?;
+        hook_runner
+            .run_graph_summary_hook(&mut graph)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        if style.cluster_contexts {
+            let mut conn = self.store.connect_database().await?;
+            graph.load_clusters(&mut conn, &style).await?;
+        }
+
+        let bytes = graph.to_bytes(self.format, &style)?;
+        if let Some(path) = &self.output {
+            std::fs::write(path, bytes)?;
+        } else {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
 use crate::cli;
+use crate::graph::GraphStyle;
+use crate::time::TimeZone;
 use crate::web;
+use anyhow::Context as _;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslVerifyMode};
 use std::path::PathBuf;
 
 #[derive(Debug, structopt::StructOpt)]
@@ -15,23 +19,107 @@ pub struct RunOpt {
 
     #[structopt(long)]
     pub hook: Option<PathBuf>,
+
+    /// Default time zone to display date-times ("local", "utc", an offset such as "+09:00" or an IANA name such as "Asia/Tokyo").
+    #[structopt(long, default_value = "local")]
+    pub time_zone: TimeZone,
+
+    /// PEM file of the server certificate chain (enables HTTPS).
+    #[structopt(long)]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM file of the server private key (enables HTTPS).
+    #[structopt(long)]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM file of the CA certificates used to verify client certificates.
+    ///
+    /// If specified, clients that don't present a valid certificate are rejected.
+    #[structopt(long)]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// plotly.js file served to draw plots in place of the bundled plotly.js v1.54.6.
+    #[structopt(long)]
+    pub plotly_js: Option<PathBuf>,
+
+    /// JSON file to persist the saved views in.
+    ///
+    /// If not specified, saved views are lost when weih stops.
+    #[structopt(long)]
+    pub views_file: Option<PathBuf>,
+
+    /// JSON file of the styles of lineage graphs (node colors and shapes per type, etc).
+    #[structopt(long)]
+    pub graph_style: Option<PathBuf>,
 }
 
 impl RunOpt {
     pub async fn execute(&self) -> anyhow::Result<()> {
         let bind_addr = std::net::SocketAddr::from((self.addr, self.port));
+        let tls = self.tls_acceptor()?;
+
+        let hook_runner =
+            crate::hook::HookRunner::load(self.hook.as_deref(), &self.store.database)?;
+        let graph_style = self
+            .graph_style
+            .as_deref()
+            .map(GraphStyle::load)
+            .transpose()
+            .with_context(|| format!("cannot load the graph style: {:?}", self.graph_style))?
+            .unwrap_or_default();
+
+        let plotly_js = self
+            .plotly_js
+            .as_ref()
+            .map(std::fs::read_to_string)
+            .transpose()?;
+        let views = web::views::ViewStore::open(self.views_file.clone())
+            .with_context(|| format!("cannot load the saved views: {:?}", self.views_file))?;
 
-        let hook_runner = if let Some(hook_config_path) = &self.hook {
-            let f = std::fs::File::open(hook_config_path)?;
-            let mut opts = Vec::new();
-            for opt in serde_json::Deserializer::from_reader(f).into_iter() {
-                opts.push(opt?);
+        web::http_server_run(web::HttpServerOpt {
+            bind_addr,
+            tls,
+            mlmd_db: self.store.database.clone(),
+            hook_runner,
+            time_zone: self.time_zone,
+            plotly_js,
+            views,
+            graph_style,
+        })
+        .await
+    }
+
+    fn tls_acceptor(&self) -> anyhow::Result<Option<SslAcceptorBuilder>> {
+        let (cert_path, key_path) = match (&self.tls_cert, &self.tls_key) {
+            (None, None) => {
+                anyhow::ensure!(
+                    self.tls_client_ca.is_none(),
+                    "`--tls-client-ca` requires `--tls-cert` and `--tls-key`"
+                );
+                return Ok(None);
             }
-            crate::hook::HookRunner::new(&opts, &self.store.database)
-        } else {
-            crate::hook::HookRunner::new(&[], &self.store.database)
+            (Some(cert), Some(key)) => (cert, key),
+            _ => anyhow::bail!("both `--tls-cert` and `--tls-key` must be specified"),
         };
 
-        web::http_server_run(bind_addr, self.store.database.clone(), hook_runner).await
+        let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+        builder
+            .set_private_key_file(key_path, SslFiletype::PEM)
+            .with_context(|| format!("cannot load the TLS private key: {:?}", key_path))?;
+        builder
+            .set_certificate_chain_file(cert_path)
+            .with_context(|| format!("cannot load the TLS certificate: {:?}", cert_path))?;
+        builder
+            .check_private_key()
+            .context("the TLS private key doesn't match the certificate")?;
+
+        if let Some(ca_path) = &self.tls_client_ca {
+            builder
+                .set_ca_file(ca_path)
+                .with_context(|| format!("cannot load the TLS client CA: {:?}", ca_path))?;
+            builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        }
+
+        Ok(Some(builder))
     }
 }
@@ -0,0 +1,1379 @@
+//! Lineage graphs of executions and artifacts, and the graph of their types.
+use crate::hook::ItemType;
+use crate::mlmd::artifact::{Artifact, ArtifactState};
+use crate::mlmd::event::Event;
+use crate::mlmd::execution::{Execution, ExecutionState};
+use crate::mlmd::hierarchy;
+use crate::mlmd::list::BATCH_SIZE;
+use crate::mlmd::type_graph::TypeEventCount;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Maximum number of nodes in a graph.
+pub const GRAPH_NODES_LIMIT: usize = 1000;
+
+/// Maximum number of characters of a name or summary shown in a node label.
+const LABEL_LINE_LIMIT: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeId {
+    Execution(i32),
+    Artifact(i32),
+}
+
+impl std::str::FromStr for NodeId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.get(..1) {
+            Some("A") => Ok(Self::Artifact(s[1..].parse()?)),
+            Some("E") => Ok(Self::Execution(s[1..].parse()?)),
+            _ => anyhow::bail!(
+                "invalid node ID: {:?} (expected `A${{ID}}` or `E${{ID}}`)",
+                s
+            ),
+        }
+    }
+}
+
+impl serde::Serialize for NodeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for NodeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Execution(x) => write!(f, "E{}", x),
+            Self::Artifact(x) => write!(f, "A{}", x),
+        }
+    }
+}
+
+/// Output format of a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    Dot,
+    Svg,
+    Png,
+    Mermaid,
+    Graphml,
+    Json,
+}
+
+impl GraphFormat {
+    pub const ALL: &'static [Self] = &[
+        Self::Dot,
+        Self::Svg,
+        Self::Png,
+        Self::Mermaid,
+        Self::Graphml,
+        Self::Json,
+    ];
+
+    pub const POSSIBLE_VALUES: &'static [&'static str] =
+        &["dot", "svg", "png", "mermaid", "graphml", "json"];
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Dot => "text/vnd.graphviz",
+            Self::Svg => "image/svg+xml",
+            Self::Png => "image/png",
+            Self::Mermaid => "text/plain",
+            Self::Graphml => "application/graphml+xml",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Svg => "svg",
+            Self::Png => "png",
+            Self::Mermaid => "mmd",
+            Self::Graphml => "graphml",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            "mermaid" => Ok(Self::Mermaid),
+            "graphml" => Ok(Self::Graphml),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("unknown graph format: {:?}", s),
+        }
+    }
+}
+
+impl std::fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Dot => write!(f, "dot"),
+            Self::Svg => write!(f, "svg"),
+            Self::Png => write!(f, "png"),
+            Self::Mermaid => write!(f, "mermaid"),
+            Self::Graphml => write!(f, "graphml"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Graphviz attributes of a node.
+///
+/// Colors are also used for Mermaid, so CSS-compatible values (e.g., `#ffcccc`) are recommended.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NodeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fillcolor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fontcolor: Option<String>,
+}
+
+impl NodeStyle {
+    /// Overwrites the attributes of `self` with those specified in `other`.
+    fn merge(&mut self, other: &Self) {
+        if other.shape.is_some() {
+            self.shape = other.shape.clone();
+        }
+        if other.color.is_some() {
+            self.color = other.color.clone();
+        }
+        if other.fillcolor.is_some() {
+            self.fillcolor = other.fillcolor.clone();
+        }
+        if other.fontcolor.is_some() {
+            self.fontcolor = other.fontcolor.clone();
+        }
+    }
+}
+
+/// Styles of lineage graphs, loaded from the JSON file given by `--graph-style`.
+///
+/// ```json
+/// {
+///   "artifact-types": {"Model": {"shape": "note", "fillcolor": "#fff2cc"}},
+///   "execution-types": {"Trainer": {"fillcolor": "#dae8fc"}},
+///   "cluster-context-types": ["Pipeline"]
+/// }
+/// ```
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GraphStyle {
+    #[serde(default)]
+    pub artifact_types: HashMap<String, NodeStyle>,
+    #[serde(default)]
+    pub execution_types: HashMap<String, NodeStyle>,
+
+    /// Whether to group the nodes by context.
+    #[serde(default = "default_cluster_contexts")]
+    pub cluster_contexts: bool,
+
+    /// Context types to group the nodes by (all types if empty).
+    #[serde(default)]
+    pub cluster_context_types: Vec<String>,
+}
+
+fn default_cluster_contexts() -> bool {
+    true
+}
+
+impl Default for GraphStyle {
+    fn default() -> Self {
+        Self {
+            artifact_types: HashMap::new(),
+            execution_types: HashMap::new(),
+            cluster_contexts: default_cluster_contexts(),
+            cluster_context_types: Vec::new(),
+        }
+    }
+}
+
+impl GraphStyle {
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let f = std::fs::File::open(path)?;
+        let style = serde_json::from_reader(std::io::BufReader::new(f))?;
+        Ok(style)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Execution {
+        node: Execution,
+        inputs: usize,
+        outputs: usize,
+    },
+    Artifact {
+        node: Artifact,
+        inputs: usize,
+        outputs: usize,
+    },
+}
+
+/// Returns the line and fill colors of an execution state that should stand out (e.g., failed), if any.
+pub fn execution_state_colors(state: ExecutionState) -> Option<(&'static str, &'static str)> {
+    match state {
+        ExecutionState::Failed => Some(("#cc0000", "#f4cccc")),
+        ExecutionState::Canceled => Some(("#b45f06", "#fce5cd")),
+        ExecutionState::Running => Some(("#1155cc", "#cfe2f3")),
+        _ => None,
+    }
+}
+
+impl Node {
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Execution { node, .. } => &node.type_name,
+            Self::Artifact { node, .. } => &node.type_name,
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        match self {
+            Self::Execution { node, .. } => NodeId::Execution(node.id),
+            Self::Artifact { node, .. } => NodeId::Artifact(node.id),
+        }
+    }
+
+    pub fn set_in_out(&mut self, events: &[mlmd::metadata::Event]) {
+        use mlmd::metadata::EventType::*;
+
+        let mut n_input = 0;
+        let mut n_output = 0;
+        for event in events {
+            match event.ty {
+                Input | DeclaredInput | InternalInput => {
+                    n_input += 1;
+                }
+                Output | DeclaredOutput | InternalOutput => {
+                    n_output += 1;
+                }
+                _ => {}
+            }
+        }
+        match self {
+            Self::Execution {
+                inputs, outputs, ..
+            } => {
+                *inputs = n_input;
+                *outputs = n_output;
+            }
+            Self::Artifact {
+                inputs, outputs, ..
+            } => {
+                *inputs = n_input;
+                *outputs = n_output;
+            }
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Execution { node, .. } => node.name.as_deref(),
+            Self::Artifact { node, .. } => node.name.as_deref(),
+        }
+    }
+
+    pub fn summary(&self) -> Option<&str> {
+        match self {
+            Self::Execution { node, .. } => node.summary.as_deref(),
+            Self::Artifact { node, .. } => node.summary.as_deref(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let (id, inputs, outputs) = match self {
+            Self::Execution {
+                node,
+                inputs,
+                outputs,
+            } => (node.id, inputs, outputs),
+            Self::Artifact {
+                node,
+                inputs,
+                outputs,
+            } => (node.id, inputs, outputs),
+        };
+        let mut lines = vec![id.to_string(), self.type_name().to_owned()];
+        if let Some(name) = self.name() {
+            lines.push(truncate_label_line(name));
+        }
+        if let Some(summary) = self.summary().and_then(|x| x.lines().next()) {
+            lines.push(truncate_label_line(summary));
+        }
+        lines.push(format!("in={},out={}", inputs, outputs));
+        lines.join("\n")
+    }
+
+    pub fn url(&self) -> String {
+        match self {
+            Self::Execution { node, .. } => format!("/executions/{}", node.id),
+            Self::Artifact { node, .. } => format!("/artifacts/{}", node.id),
+        }
+    }
+
+    pub fn shape(&self) -> String {
+        match self {
+            Self::Execution { .. } => "box".to_owned(),
+            Self::Artifact { .. } => "ellipse".to_owned(),
+        }
+    }
+
+    /// Gets the style of this node.
+    ///
+    /// The style of the node type in `style` overrides the default shape,
+    /// and the colors of abnormal states (e.g., failed executions) override the style of the type.
+    pub fn style(&self, style: &GraphStyle) -> NodeStyle {
+        let mut node_style = NodeStyle {
+            shape: Some(self.shape()),
+            ..Default::default()
+        };
+        let type_style = match self {
+            Self::Execution { .. } => style.execution_types.get(self.type_name()),
+            Self::Artifact { .. } => style.artifact_types.get(self.type_name()),
+        };
+        if let Some(type_style) = type_style {
+            node_style.merge(type_style);
+        }
+        node_style.merge(&self.state_style());
+        node_style
+    }
+
+    fn state_style(&self) -> NodeStyle {
+        let (color, fillcolor, fontcolor) = match self {
+            Self::Execution { node, .. } => match execution_state_colors(node.state) {
+                Some((color, fillcolor)) => (color, fillcolor, None),
+                None => return NodeStyle::default(),
+            },
+            Self::Artifact { node, .. } => match node.state {
+                ArtifactState::MarkedForDeletion | ArtifactState::Deleted => {
+                    ("#999999", "#eeeeee", Some("#666666"))
+                }
+                _ => return NodeStyle::default(),
+            },
+        };
+        NodeStyle {
+            shape: None,
+            color: Some(color.to_owned()),
+            fillcolor: Some(fillcolor.to_owned()),
+            fontcolor: fontcolor.map(|x| x.to_owned()),
+        }
+    }
+
+    pub fn attrs(&self, style: &GraphStyle) -> Vec<String> {
+        let style = self.style(style);
+        let mut attrs = vec![
+            format!("label={:?}", self.label()),
+            format!("URL={:?}", self.url()),
+        ];
+        if let Some(x) = &style.shape {
+            attrs.push(format!("shape={:?}", x));
+        }
+        if let Some(x) = &style.color {
+            attrs.push(format!("color={:?}", x));
+        }
+        if let Some(x) = &style.fillcolor {
+            attrs.push(format!("style=filled,fillcolor={:?}", x));
+        }
+        if let Some(x) = &style.fontcolor {
+            attrs.push(format!("fontcolor={:?}", x));
+        }
+        attrs
+    }
+}
+
+fn truncate_label_line(s: &str) -> String {
+    if s.chars().count() <= LABEL_LINE_LIMIT {
+        s.to_owned()
+    } else {
+        let mut s = s.chars().take(LABEL_LINE_LIMIT - 1).collect::<String>();
+        s.push('…');
+        s
+    }
+}
+
+impl serde::Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        #[derive(serde::Serialize)]
+        struct NodeJson<'a> {
+            id: NodeId,
+            #[serde(rename = "type")]
+            type_name: &'a str,
+            name: Option<&'a str>,
+            url: String,
+            inputs: usize,
+            outputs: usize,
+        }
+
+        let (name, inputs, outputs) = match self {
+            Self::Execution {
+                node,
+                inputs,
+                outputs,
+            } => (node.name.as_deref(), *inputs, *outputs),
+            Self::Artifact {
+                node,
+                inputs,
+                outputs,
+            } => (node.name.as_deref(), *inputs, *outputs),
+        };
+        NodeJson {
+            id: self.id(),
+            type_name: self.type_name(),
+            name,
+            url: self.url(),
+            inputs,
+            outputs,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Edge {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub event: Event,
+}
+
+/// Nodes that belong to the same context.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Cluster {
+    pub context_id: i32,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub name: String,
+    pub nodes: Vec<NodeId>,
+}
+
+impl Cluster {
+    pub fn label(&self) -> String {
+        format!("{}: {}", self.type_name, self.name)
+    }
+
+    pub fn url(&self) -> String {
+        format!("/contexts/{}", self.context_id)
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clusters: Vec<Cluster>,
+}
+
+impl Graph {
+    /// Builds the provenance graph of `root`.
+    ///
+    /// All the inputs and outputs of `root` are included,
+    /// and the other nodes are expanded upstream (i.e., to producers and inputs).
+    /// The graph is expanded breadth-first, fetching each level of nodes in batches.
+    pub async fn new(store: &mut mlmd::MetadataStore, root: NodeId) -> anyhow::Result<Self> {
+        let mut store = BatchStore::new(store);
+        let mut nodes = HashMap::new();
+        let mut edges = Vec::new();
+        let mut visited_events = HashSet::new();
+        let mut visited = HashSet::new();
+        visited.insert(root);
+        let mut frontier = vec![root];
+        while !frontier.is_empty() {
+            anyhow::ensure!(
+                visited.len() <= GRAPH_NODES_LIMIT,
+                "too many executions and artifacts to visualize (> {})",
+                GRAPH_NODES_LIMIT
+            );
+
+            let mut events: HashMap<_, Vec<_>> = HashMap::new();
+            for (id, event) in store.get_events(&frontier).await? {
+                events.entry(id).or_default().push(event);
+            }
+            for mut node in store.get_nodes(&frontier).await? {
+                node.set_in_out(events.get(&node.id()).map_or(&[][..], |x| x.as_slice()));
+                nodes.insert(node.id(), node);
+            }
+
+            let mut next = Vec::new();
+            for (id, events) in events {
+                for event in events {
+                    use mlmd::metadata::EventType::*;
+
+                    let is_output = matches!(event.ty, Output | DeclaredOutput | InternalOutput);
+                    let is_input = matches!(event.ty, Input | DeclaredInput | InternalInput);
+                    let (follow, neighbor) = match id {
+                        NodeId::Artifact(_) => (
+                            id == root || is_output,
+                            NodeId::Execution(event.execution_id.get()),
+                        ),
+                        NodeId::Execution(_) => (
+                            id == root || is_input,
+                            NodeId::Artifact(event.artifact_id.get()),
+                        ),
+                    };
+                    if !follow {
+                        continue;
+                    }
+
+                    let event = Event::from(event);
+                    if visited_events.insert((event.artifact_id, event.execution_id, event.ty)) {
+                        let artifact = NodeId::Artifact(event.artifact_id);
+                        let execution = NodeId::Execution(event.execution_id);
+                        let (source, target) = if is_output {
+                            (execution, artifact)
+                        } else {
+                            (artifact, execution)
+                        };
+                        edges.push(Edge {
+                            source,
+                            target,
+                            event,
+                        });
+                    }
+                    if visited.insert(neighbor) {
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        Ok(Self {
+            nodes: nodes.into_iter().map(|x| x.1).collect(),
+            edges,
+            clusters: Vec::new(),
+        })
+    }
+
+    /// Builds the graph of `center` and its immediate neighbours (both inputs and outputs).
+    pub async fn neighbors(
+        store: &mut mlmd::MetadataStore,
+        center: NodeId,
+    ) -> anyhow::Result<Self> {
+        let mut store = BatchStore::new(store);
+        let mut ids = vec![center];
+        let mut edges = Vec::new();
+        for (_, event) in store.get_events(&[center]).await? {
+            let event = Event::from(event);
+            let artifact = NodeId::Artifact(event.artifact_id);
+            let execution = NodeId::Execution(event.execution_id);
+            let neighbor = if center == artifact {
+                execution
+            } else {
+                artifact
+            };
+            if !ids.contains(&neighbor) {
+                ids.push(neighbor);
+            }
+            let (source, target) = if event.ty.is_output() {
+                (execution, artifact)
+            } else {
+                (artifact, execution)
+            };
+            edges.push(Edge {
+                source,
+                target,
+                event,
+            });
+        }
+        anyhow::ensure!(
+            ids.len() <= GRAPH_NODES_LIMIT,
+            "too many neighbours to visualize (> {})",
+            GRAPH_NODES_LIMIT
+        );
+
+        // The events of the neighbours are only used to count their inputs and outputs.
+        let mut events: HashMap<_, Vec<_>> = HashMap::new();
+        for (id, event) in store.get_events(&ids).await? {
+            events.entry(id).or_default().push(event);
+        }
+        let mut nodes = store.get_nodes(&ids).await?;
+        for node in &mut nodes {
+            node.set_in_out(events.get(&node.id()).map_or(&[][..], |x| x.as_slice()));
+        }
+        Ok(Self {
+            nodes,
+            edges,
+            clusters: Vec::new(),
+        })
+    }
+
+    /// Groups the nodes by context (see [`GraphStyle::cluster_context_types`]).
+    ///
+    /// Clusters can't overlap, so a node that belongs to several contexts
+    /// is put in the one with the smallest ID.
+    pub async fn load_clusters(
+        &mut self,
+        conn: &mut sqlx::AnyConnection,
+        style: &GraphStyle,
+    ) -> anyhow::Result<()> {
+        let ids = self.nodes.iter().map(|x| x.id()).collect::<Vec<_>>();
+        let (artifact_ids, execution_ids) = split_node_ids(&ids);
+        let memberships = hierarchy::get_artifact_memberships(conn, &artifact_ids)
+            .await?
+            .into_iter()
+            .map(|x| (NodeId::Artifact(x.item_id), x))
+            .chain(
+                hierarchy::get_execution_memberships(conn, &execution_ids)
+                    .await?
+                    .into_iter()
+                    .map(|x| (NodeId::Execution(x.item_id), x)),
+            );
+
+        let mut node_contexts: HashMap<NodeId, hierarchy::Membership> = HashMap::new();
+        for (id, context) in memberships {
+            if !style.cluster_context_types.is_empty()
+                && !style.cluster_context_types.contains(&context.context_type)
+            {
+                continue;
+            }
+            match node_contexts.get(&id) {
+                Some(x) if x.context_id <= context.context_id => {}
+                _ => {
+                    node_contexts.insert(id, context);
+                }
+            }
+        }
+
+        let mut clusters: BTreeMap<i32, Cluster> = BTreeMap::new();
+        for id in ids {
+            if let Some(context) = node_contexts.remove(&id) {
+                clusters
+                    .entry(context.context_id)
+                    .or_insert_with(|| Cluster {
+                        context_id: context.context_id,
+                        type_name: context.context_type,
+                        name: context.context_name,
+                        nodes: Vec::new(),
+                    })
+                    .nodes
+                    .push(id);
+            }
+        }
+        self.clusters = clusters.into_iter().map(|x| x.1).collect();
+        Ok(())
+    }
+
+    /// Partitions the nodes into the clusters (in the order of `self.clusters`)
+    /// and the nodes that don't belong to any cluster (the last element).
+    fn cluster_nodes(&self) -> Vec<Vec<&Node>> {
+        let mut index = HashMap::new();
+        for (i, cluster) in self.clusters.iter().enumerate() {
+            for id in &cluster.nodes {
+                index.insert(*id, i);
+            }
+        }
+        let mut groups = vec![Vec::new(); self.clusters.len() + 1];
+        for node in &self.nodes {
+            let i = index
+                .get(&node.id())
+                .copied()
+                .unwrap_or(self.clusters.len());
+            groups[i].push(node);
+        }
+        groups
+    }
+
+    pub fn render<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        style: &GraphStyle,
+    ) -> anyhow::Result<()> {
+        writeln!(writer, "digraph execution_graph {{")?;
+
+        let mut groups = self.cluster_nodes();
+        for node in groups.pop().expect("unreachable") {
+            writeln!(writer, "{}[{}]", node.id(), node.attrs(style).join(","))?;
+        }
+        for (cluster, nodes) in self.clusters.iter().zip(groups) {
+            writeln!(writer, "subgraph cluster_C{} {{", cluster.context_id)?;
+            writeln!(
+                writer,
+                "label={:?};URL={:?};style=\"rounded,dashed\";",
+                cluster.label(),
+                cluster.url()
+            )?;
+            for node in nodes {
+                writeln!(writer, "{}[{}]", node.id(), node.attrs(style).join(","))?;
+            }
+            writeln!(writer, "}}")?;
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "{} -> {} [label={:?}];",
+                edge.source,
+                edge.target,
+                format!("{:?}:{:?}", edge.event.ty, edge.event.path)
+            )?;
+        }
+
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
+    /// Writes the graph as a Mermaid flowchart.
+    ///
+    /// Executions are drawn as boxes, artifacts as stadiums (rounded boxes) and clusters as
+    /// subgraphs. Shapes of `style` aren't supported by Mermaid, but colors are.
+    pub fn render_mermaid<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        style: &GraphStyle,
+    ) -> anyhow::Result<()> {
+        writeln!(writer, "flowchart LR")?;
+
+        let mut groups = self.cluster_nodes();
+        let unclustered = groups.pop().expect("unreachable");
+        for node in &unclustered {
+            write_mermaid_node(writer, node, "    ")?;
+        }
+        for (cluster, nodes) in self.clusters.iter().zip(groups) {
+            writeln!(
+                writer,
+                "    subgraph C{}[\"{}\"]",
+                cluster.context_id,
+                mermaid_escape(&cluster.label())
+            )?;
+            for node in &nodes {
+                write_mermaid_node(writer, node, "        ")?;
+            }
+            writeln!(writer, "    end")?;
+        }
+
+        for node in &self.nodes {
+            writeln!(writer, "    click {} \"{}\"", node.id(), node.url())?;
+            let node_style = node.style(style);
+            let mut props = Vec::new();
+            if let Some(x) = &node_style.fillcolor {
+                props.push(format!("fill:{}", x));
+            }
+            if let Some(x) = &node_style.color {
+                props.push(format!("stroke:{}", x));
+            }
+            if let Some(x) = &node_style.fontcolor {
+                props.push(format!("color:{}", x));
+            }
+            if !props.is_empty() {
+                writeln!(writer, "    style {} {}", node.id(), props.join(","))?;
+            }
+        }
+
+        for edge in &self.edges {
+            let mut label = edge.event.ty.to_string();
+            if !edge.event.path.is_empty() {
+                label.push(' ');
+                label.push_str(&edge.event.path_string());
+            }
+            writeln!(
+                writer,
+                "    {} -->|\"{}\"| {}",
+                edge.source,
+                mermaid_escape(&label),
+                edge.target
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the graph as GraphML.
+    pub fn render_graphml<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        for (id, target) in [
+            ("kind", "node"),
+            ("type", "node"),
+            ("name", "node"),
+            ("url", "node"),
+            ("context", "node"),
+            ("event_type", "edge"),
+            ("path", "edge"),
+        ] {
+            writeln!(
+                writer,
+                r#"  <key id="{0}" for="{1}" attr.name="{0}" attr.type="string"/>"#,
+                id, target
+            )?;
+        }
+        writeln!(writer, r#"  <graph id="lineage" edgedefault="directed">"#)?;
+
+        let mut node_clusters = HashMap::new();
+        for cluster in &self.clusters {
+            for id in &cluster.nodes {
+                node_clusters.insert(*id, cluster);
+            }
+        }
+
+        for node in &self.nodes {
+            let (kind, name) = match node {
+                Node::Execution { node, .. } => ("execution", node.name.as_deref()),
+                Node::Artifact { node, .. } => ("artifact", node.name.as_deref()),
+            };
+            writeln!(writer, r#"    <node id="{}">"#, node.id())?;
+            writeln!(writer, r#"      <data key="kind">{}</data>"#, kind)?;
+            writeln!(
+                writer,
+                r#"      <data key="type">{}</data>"#,
+                xml_escape(node.type_name())
+            )?;
+            if let Some(name) = name {
+                writeln!(
+                    writer,
+                    r#"      <data key="name">{}</data>"#,
+                    xml_escape(name)
+                )?;
+            }
+            writeln!(
+                writer,
+                r#"      <data key="url">{}</data>"#,
+                xml_escape(&node.url())
+            )?;
+            if let Some(cluster) = node_clusters.get(&node.id()) {
+                writeln!(
+                    writer,
+                    r#"      <data key="context">{}</data>"#,
+                    xml_escape(&cluster.label())
+                )?;
+            }
+            writeln!(writer, "    </node>")?;
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                r#"    <edge source="{}" target="{}">"#,
+                edge.source, edge.target
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="event_type">{}</data>"#,
+                edge.event.ty
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="path">{}</data>"#,
+                xml_escape(&edge.event.path_string())
+            )?;
+            writeln!(writer, "    </edge>")?;
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        Ok(())
+    }
+
+    /// Renders the graph to an image by piping its DOT representation to `dot`.
+    ///
+    /// `format` is passed as `dot -T${format}` (e.g., "svg" or "png").
+    pub fn render_image(&self, format: &str, style: &GraphStyle) -> anyhow::Result<Vec<u8>> {
+        let mut dot = Vec::new();
+        self.render(&mut dot, style)?;
+        run_dot(&dot, format)
+    }
+
+    /// Serializes the graph in `format`.
+    pub fn to_bytes(&self, format: GraphFormat, style: &GraphStyle) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match format {
+            GraphFormat::Dot => self.render(&mut buf, style)?,
+            GraphFormat::Svg => buf = self.render_image("svg", style)?,
+            GraphFormat::Png => buf = self.render_image("png", style)?,
+            GraphFormat::Mermaid => self.render_mermaid(&mut buf, style)?,
+            GraphFormat::Graphml => self.render_graphml(&mut buf)?,
+            GraphFormat::Json => serde_json::to_writer_pretty(&mut buf, self)?,
+        }
+        Ok(buf)
+    }
+}
+
+/// Renders `dot` (a graph in the DOT language) to an image by `dot -T${format}`.
+fn run_dot(dot: &[u8], format: &str) -> anyhow::Result<Vec<u8>> {
+    use anyhow::Context as _;
+    use std::io::Write as _;
+
+    let mut child = std::process::Command::new("dot")
+        .arg(format!("-T{}", format))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .context("cannot execute `dot` (is Graphviz installed?)")?;
+    {
+        let mut writer = child.stdin.take().expect("unreachable");
+        writer.write_all(dot)?;
+        writer.flush()?;
+    }
+    let output = child.wait_with_output()?;
+    anyhow::ensure!(
+        output.status.success(),
+        "`dot -T{}` failed: {}",
+        format,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(output.stdout)
+}
+
+/// Node of a [`TypeGraph`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TypeNode {
+    pub kind: ItemType,
+    pub type_id: i32,
+    pub name: String,
+}
+
+impl TypeNode {
+    /// Returns the ID of this node, such as `AT3` (artifact type) or `ET5` (execution type).
+    pub fn node_id(&self) -> String {
+        match self.kind {
+            ItemType::Artifact => format!("AT{}", self.type_id),
+            ItemType::Execution => format!("ET{}", self.type_id),
+            ItemType::Context => format!("CT{}", self.type_id),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        match self.kind {
+            ItemType::Artifact => format!("/artifact_types/{}", self.type_id),
+            ItemType::Execution => format!("/execution_types/{}", self.type_id),
+            ItemType::Context => format!("/context_types/{}", self.type_id),
+        }
+    }
+}
+
+/// Edge of a [`TypeGraph`].
+///
+/// Input events make edges from artifact types to execution types, and output events the reverse.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TypeEdge {
+    pub source: String,
+    pub target: String,
+    pub count: i64,
+}
+
+/// Graph of the artifact and execution types, aggregated from all the events in a store.
+#[derive(Debug, serde::Serialize)]
+pub struct TypeGraph {
+    pub nodes: Vec<TypeNode>,
+    pub edges: Vec<TypeEdge>,
+}
+
+impl TypeGraph {
+    /// Builds the graph from the event counts of each pair of types.
+    ///
+    /// Events that are neither inputs nor outputs (i.e., `UNKNOWN`) are ignored.
+    pub async fn new(
+        store: &mut mlmd::MetadataStore,
+        counts: &[TypeEventCount],
+    ) -> anyhow::Result<Self> {
+        let mut edges: BTreeMap<(String, String), i64> = BTreeMap::new();
+        let mut artifact_type_ids = HashSet::new();
+        let mut execution_type_ids = HashSet::new();
+        for x in counts {
+            let artifact = format!("AT{}", x.artifact_type_id);
+            let execution = format!("ET{}", x.execution_type_id);
+            let key = if x.event_type.is_input() {
+                (artifact, execution)
+            } else if x.event_type.is_output() {
+                (execution, artifact)
+            } else {
+                continue;
+            };
+            *edges.entry(key).or_default() += x.count;
+            artifact_type_ids.insert(x.artifact_type_id);
+            execution_type_ids.insert(x.execution_type_id);
+        }
+
+        let mut nodes = Vec::new();
+        if !artifact_type_ids.is_empty() {
+            for ty in store
+                .get_artifact_types()
+                .ids(
+                    artifact_type_ids
+                        .into_iter()
+                        .map(mlmd::metadata::TypeId::new),
+                )
+                .execute()
+                .await?
+            {
+                nodes.push(TypeNode {
+                    kind: ItemType::Artifact,
+                    type_id: ty.id.get(),
+                    name: ty.name,
+                });
+            }
+        }
+        if !execution_type_ids.is_empty() {
+            for ty in store
+                .get_execution_types()
+                .ids(
+                    execution_type_ids
+                        .into_iter()
+                        .map(mlmd::metadata::TypeId::new),
+                )
+                .execute()
+                .await?
+            {
+                nodes.push(TypeNode {
+                    kind: ItemType::Execution,
+                    type_id: ty.id.get(),
+                    name: ty.name,
+                });
+            }
+        }
+        nodes.sort_by_key(|x| x.node_id());
+
+        Ok(Self {
+            nodes,
+            edges: edges
+                .into_iter()
+                .map(|((source, target), count)| TypeEdge {
+                    source,
+                    target,
+                    count,
+                })
+                .collect(),
+        })
+    }
+
+    /// Writes the graph in DOT.
+    ///
+    /// The widths of the edges grow logarithmically with the event counts.
+    pub fn render<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writeln!(writer, "digraph type_graph {{")?;
+        writeln!(writer, "rankdir=LR;")?;
+
+        for node in &self.nodes {
+            let shape = match node.kind {
+                ItemType::Execution => "box",
+                _ => "ellipse",
+            };
+            writeln!(
+                writer,
+                "{}[label={:?},shape={:?},URL={:?}]",
+                node.node_id(),
+                node.name,
+                shape,
+                node.url()
+            )?;
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "{} -> {} [label=\"{}\",penwidth={:.1}];",
+                edge.source,
+                edge.target,
+                edge.count,
+                1.0 + (edge.count as f64).log10()
+            )?;
+        }
+
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
+    /// Writes the graph as a Mermaid flowchart.
+    pub fn render_mermaid<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writeln!(writer, "flowchart LR")?;
+        for node in &self.nodes {
+            let name = mermaid_escape(&node.name);
+            match node.kind {
+                ItemType::Execution => writeln!(writer, "    {}[\"{}\"]", node.node_id(), name)?,
+                _ => writeln!(writer, "    {}([\"{}\"])", node.node_id(), name)?,
+            }
+            writeln!(writer, "    click {} \"{}\"", node.node_id(), node.url())?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "    {} -->|{}| {}",
+                edge.source, edge.count, edge.target
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the graph as GraphML.
+    pub fn render_graphml<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        for (id, target, ty) in [
+            ("kind", "node", "string"),
+            ("name", "node", "string"),
+            ("url", "node", "string"),
+            ("count", "edge", "long"),
+        ] {
+            writeln!(
+                writer,
+                r#"  <key id="{0}" for="{1}" attr.name="{0}" attr.type="{2}"/>"#,
+                id, target, ty
+            )?;
+        }
+        writeln!(writer, r#"  <graph id="types" edgedefault="directed">"#)?;
+        for node in &self.nodes {
+            writeln!(writer, r#"    <node id="{}">"#, node.node_id())?;
+            let kind = match node.kind {
+                ItemType::Artifact => "artifact",
+                ItemType::Execution => "execution",
+                ItemType::Context => "context",
+            };
+            writeln!(writer, r#"      <data key="kind">{}</data>"#, kind)?;
+            writeln!(
+                writer,
+                r#"      <data key="name">{}</data>"#,
+                xml_escape(&node.name)
+            )?;
+            writeln!(writer, r#"      <data key="url">{}</data>"#, node.url())?;
+            writeln!(writer, "    </node>")?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                r#"    <edge source="{}" target="{}">"#,
+                edge.source, edge.target
+            )?;
+            writeln!(writer, r#"      <data key="count">{}</data>"#, edge.count)?;
+            writeln!(writer, "    </edge>")?;
+        }
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        Ok(())
+    }
+
+    /// Serializes the graph in `format`.
+    pub fn to_bytes(&self, format: GraphFormat) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match format {
+            GraphFormat::Dot => self.render(&mut buf)?,
+            GraphFormat::Svg | GraphFormat::Png => {
+                self.render(&mut buf)?;
+                buf = run_dot(&buf, &format.to_string())?;
+            }
+            GraphFormat::Mermaid => self.render_mermaid(&mut buf)?,
+            GraphFormat::Graphml => self.render_graphml(&mut buf)?,
+            GraphFormat::Json => serde_json::to_writer_pretty(&mut buf, self)?,
+        }
+        Ok(buf)
+    }
+}
+
+fn write_mermaid_node<W: std::io::Write>(
+    writer: &mut W,
+    node: &Node,
+    indent: &str,
+) -> anyhow::Result<()> {
+    let label = mermaid_escape(&node.label().replace('\n', "<br>"));
+    match node {
+        Node::Execution { .. } => writeln!(writer, "{}{}[\"{}\"]", indent, node.id(), label)?,
+        Node::Artifact { .. } => writeln!(writer, "{}{}([\"{}\"])", indent, node.id(), label)?,
+    }
+    Ok(())
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Metadata store that fetches nodes and events in batches.
+///
+/// The types of the fetched nodes are cached during the lifetime of this store (i.e., a request).
+pub struct BatchStore<'a> {
+    store: &'a mut mlmd::MetadataStore,
+    artifact_types: HashMap<mlmd::metadata::TypeId, mlmd::metadata::ArtifactType>,
+    execution_types: HashMap<mlmd::metadata::TypeId, mlmd::metadata::ExecutionType>,
+}
+
+impl<'a> BatchStore<'a> {
+    pub fn new(store: &'a mut mlmd::MetadataStore) -> Self {
+        Self {
+            store,
+            artifact_types: HashMap::new(),
+            execution_types: HashMap::new(),
+        }
+    }
+
+    /// Gets the events of `nodes` paired with the node each event was fetched for.
+    pub async fn get_events(
+        &mut self,
+        nodes: &[NodeId],
+    ) -> anyhow::Result<Vec<(NodeId, mlmd::metadata::Event)>> {
+        let (artifact_ids, execution_ids) = split_node_ids(nodes);
+        let mut events = Vec::new();
+        for ids in artifact_ids.chunks(BATCH_SIZE) {
+            let ids = ids.iter().copied().map(mlmd::metadata::ArtifactId::new);
+            for event in self.store.get_events().artifacts(ids).execute().await? {
+                events.push((NodeId::Artifact(event.artifact_id.get()), event));
+            }
+        }
+        for ids in execution_ids.chunks(BATCH_SIZE) {
+            let ids = ids.iter().copied().map(mlmd::metadata::ExecutionId::new);
+            for event in self.store.get_events().executions(ids).execute().await? {
+                events.push((NodeId::Execution(event.execution_id.get()), event));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Gets the nodes of `ids` (nodes that don't exist are ignored).
+    pub async fn get_nodes(&mut self, ids: &[NodeId]) -> anyhow::Result<Vec<Node>> {
+        let (artifact_ids, execution_ids) = split_node_ids(ids);
+        let mut nodes = Vec::new();
+        for node in self.get_artifacts(&artifact_ids).await? {
+            nodes.push(Node::Artifact {
+                node,
+                inputs: 0,
+                outputs: 0,
+            });
+        }
+        for node in self.get_executions(&execution_ids).await? {
+            nodes.push(Node::Execution {
+                node,
+                inputs: 0,
+                outputs: 0,
+            });
+        }
+        Ok(nodes)
+    }
+
+    pub async fn get_artifacts(&mut self, ids: &[i32]) -> anyhow::Result<Vec<Artifact>> {
+        let mut artifacts = Vec::new();
+        for ids in ids.chunks(BATCH_SIZE) {
+            artifacts.extend(
+                self.store
+                    .get_artifacts()
+                    .ids(ids.iter().copied().map(mlmd::metadata::ArtifactId::new))
+                    .execute()
+                    .await?,
+            );
+        }
+
+        let missing_type_ids = artifacts
+            .iter()
+            .map(|x| x.type_id)
+            .filter(|id| !self.artifact_types.contains_key(id))
+            .collect::<HashSet<_>>();
+        if !missing_type_ids.is_empty() {
+            for ty in self
+                .store
+                .get_artifact_types()
+                .ids(missing_type_ids.into_iter())
+                .execute()
+                .await?
+            {
+                self.artifact_types.insert(ty.id, ty);
+            }
+        }
+
+        artifacts
+            .into_iter()
+            .map(|x| {
+                let ty = self
+                    .artifact_types
+                    .get(&x.type_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("no such artifact type: {}", x.type_id.get()))?;
+                Ok(Artifact::from((ty, x)))
+            })
+            .collect()
+    }
+
+    pub async fn get_executions(&mut self, ids: &[i32]) -> anyhow::Result<Vec<Execution>> {
+        let mut executions = Vec::new();
+        for ids in ids.chunks(BATCH_SIZE) {
+            executions.extend(
+                self.store
+                    .get_executions()
+                    .ids(ids.iter().copied().map(mlmd::metadata::ExecutionId::new))
+                    .execute()
+                    .await?,
+            );
+        }
+
+        let missing_type_ids = executions
+            .iter()
+            .map(|x| x.type_id)
+            .filter(|id| !self.execution_types.contains_key(id))
+            .collect::<HashSet<_>>();
+        if !missing_type_ids.is_empty() {
+            for ty in self
+                .store
+                .get_execution_types()
+                .ids(missing_type_ids.into_iter())
+                .execute()
+                .await?
+            {
+                self.execution_types.insert(ty.id, ty);
+            }
+        }
+
+        executions
+            .into_iter()
+            .map(|x| {
+                let ty = self
+                    .execution_types
+                    .get(&x.type_id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("no such execution type: {}", x.type_id.get())
+                    })?;
+                Ok(Execution::from((ty, x)))
+            })
+            .collect()
+    }
+}
+
+fn split_node_ids(nodes: &[NodeId]) -> (Vec<i32>, Vec<i32>) {
+    let mut artifact_ids = Vec::new();
+    let mut execution_ids = Vec::new();
+    for node in nodes {
+        match *node {
+            NodeId::Artifact(id) => artifact_ids.push(id),
+            NodeId::Execution(id) => execution_ids.push(id),
+        }
+    }
+    (artifact_ids, execution_ids)
+}
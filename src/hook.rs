@@ -52,6 +52,18 @@ impl HookRunner {
         Self { hooks }
     }
 
+    /// Loads the hooks from `path`, a JSON stream of [`HookOpt`] (no hooks if `None`).
+    pub fn load(path: Option<&std::path::Path>, metadata_store_uri: &str) -> anyhow::Result<Self> {
+        let mut opts = Vec::new();
+        if let Some(path) = path {
+            let f = std::fs::File::open(path)?;
+            for opt in serde_json::Deserializer::from_reader(f).into_iter() {
+                opts.push(opt?);
+            }
+        }
+        Ok(Self::new(&opts, metadata_store_uri))
+    }
+
     pub async fn run(&self, input: HookInput) -> actix_web::error::Result<Option<HookOutput>> {
         let item_type = input.item_type();
         let type_name = input.type_name().to_owned();
@@ -188,6 +200,50 @@ impl HookRunner {
         Ok(result)
     }
 
+    /// Fills the summaries of the nodes of `graph` by the artifact and execution summary hooks.
+    pub async fn run_graph_summary_hook(
+        &self,
+        graph: &mut crate::graph::Graph,
+    ) -> actix_web::error::Result<()> {
+        use crate::graph::Node;
+
+        let mut artifacts = Vec::new();
+        let mut executions = Vec::new();
+        for node in &graph.nodes {
+            match node {
+                Node::Artifact { node, .. } => artifacts.push(node.clone()),
+                Node::Execution { node, .. } => executions.push(node.clone()),
+            }
+        }
+        let mut artifacts = self
+            .run_artifact_summary_hook(artifacts)
+            .await?
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+        let mut executions = self
+            .run_execution_summary_hook(executions)
+            .await?
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+        for node in &mut graph.nodes {
+            match node {
+                Node::Artifact { node, .. } => {
+                    if let Some(x) = artifacts.remove(&node.id) {
+                        *node = x;
+                    }
+                }
+                Node::Execution { node, .. } => {
+                    if let Some(x) = executions.remove(&node.id) {
+                        *node = x;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub async fn run_execution_detail_hook(
         &self,
         execution: crate::mlmd::execution::Execution,
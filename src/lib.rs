@@ -1,4 +1,5 @@
 pub mod cli;
+pub mod graph;
 pub mod hook;
 pub mod mlmd;
 pub mod time;
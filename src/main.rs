@@ -3,6 +3,7 @@ use structopt::StructOpt;
 #[derive(Debug, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 enum Opt {
+    Graph(weih::cli::graph::GraphOpt),
     Hook(weih::cli::hook::HookOpt),
     Run(weih::cli::run::RunOpt),
 }
@@ -11,6 +12,7 @@ enum Opt {
 async fn main() -> anyhow::Result<()> {
     let opt = Opt::from_args();
     match opt {
+        Opt::Graph(o) => o.execute().await?,
         Opt::Hook(o) => o.execute().await?,
         Opt::Run(o) => o.execute().await?,
     }
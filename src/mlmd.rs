@@ -1,5 +1,16 @@
+use sqlx::Connection as _;
+
 pub mod artifact;
 pub mod context;
 pub mod event;
 pub mod execution;
+pub mod hierarchy;
+pub mod list;
 pub mod property;
+pub mod stats;
+pub mod type_graph;
+
+/// Connects to the database of a metadata store directly (for queries that the `mlmd` crate lacks).
+pub async fn connect(uri: &str) -> anyhow::Result<sqlx::AnyConnection> {
+    Ok(sqlx::AnyConnection::connect(uri).await?)
+}
@@ -15,7 +15,7 @@ impl From<mlmd::metadata::ArtifactType> for ArtifactTypeSummary {
         Self {
             id: x.id.get(),
             name: x.name,
-            properties: x.properties.into_iter().map(|(k, _)| k).collect(),
+            properties: x.properties.into_keys().collect(),
         }
     }
 }
@@ -129,9 +129,10 @@ impl From<mlmd::metadata::ArtifactState> for ArtifactState {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum ArtifactOrderByField {
+    #[default]
     Id,
     Name,
     CreateTime,
@@ -142,12 +143,6 @@ impl ArtifactOrderByField {
     pub const POSSIBLE_VALUES: &'static [&'static str] = &["id", "name", "ctime", "mtime"];
 }
 
-impl Default for ArtifactOrderByField {
-    fn default() -> Self {
-        Self::Id
-    }
-}
-
 impl std::str::FromStr for ArtifactOrderByField {
     type Err = anyhow::Error;
 
@@ -198,7 +193,7 @@ impl ArtifactIdOrName {
                     .type_and_name(type_name, artifact_name)
                     .execute()
                     .await?;
-                if let Some(id) = artifacts.get(0).map(|c| c.id) {
+                if let Some(id) = artifacts.first().map(|c| c.id) {
                     Ok(id)
                 } else {
                     anyhow::bail!(
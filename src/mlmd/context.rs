@@ -15,7 +15,7 @@ impl From<mlmd::metadata::ContextType> for ContextTypeSummary {
         Self {
             id: x.id.get(),
             name: x.name,
-            properties: x.properties.into_iter().map(|(k, _)| k).collect(),
+            properties: x.properties.into_keys().collect(),
         }
     }
 }
@@ -66,7 +66,7 @@ impl ContextIdOrName {
                     .type_and_name(type_name, context_name)
                     .execute()
                     .await?;
-                if let Some(id) = contexts.get(0).map(|c| c.id) {
+                if let Some(id) = contexts.first().map(|c| c.id) {
                     Ok(id)
                 } else {
                     anyhow::bail!(
@@ -140,9 +140,10 @@ impl From<(mlmd::metadata::ContextType, mlmd::metadata::Context)> for Context {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum ContextOrderByField {
+    #[default]
     Id,
     Name,
     CreateTime,
@@ -153,12 +154,6 @@ impl ContextOrderByField {
     pub const POSSIBLE_VALUES: &'static [&'static str] = &["id", "name", "ctime", "mtime"];
 }
 
-impl Default for ContextOrderByField {
-    fn default() -> Self {
-        Self::Id
-    }
-}
-
 impl std::str::FromStr for ContextOrderByField {
     type Err = anyhow::Error;
 
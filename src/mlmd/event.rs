@@ -9,6 +9,21 @@ pub struct Event {
     pub time: DateTime,
 }
 
+impl Event {
+    /// Formats the path like `examples[0]`.
+    pub fn path_string(&self) -> String {
+        let mut s = String::new();
+        for step in &self.path {
+            match step {
+                EventStep::Index(x) => s += &format!("[{}]", x),
+                EventStep::Key(x) if s.is_empty() => s += x,
+                EventStep::Key(x) => s += &format!(".{}", x),
+            }
+        }
+        s
+    }
+}
+
 impl From<mlmd::metadata::Event> for Event {
     fn from(x: mlmd::metadata::Event) -> Self {
         Self {
@@ -33,6 +48,46 @@ pub enum EventType {
     InternalOutput,
 }
 
+impl EventType {
+    /// All the event types, inputs first.
+    pub const ALL: &'static [Self] = &[
+        Self::DeclaredInput,
+        Self::Input,
+        Self::InternalInput,
+        Self::DeclaredOutput,
+        Self::Output,
+        Self::InternalOutput,
+        Self::Unknown,
+    ];
+
+    pub fn is_input(self) -> bool {
+        matches!(
+            self,
+            Self::DeclaredInput | Self::Input | Self::InternalInput
+        )
+    }
+
+    pub fn is_output(self) -> bool {
+        matches!(
+            self,
+            Self::DeclaredOutput | Self::Output | Self::InternalOutput
+        )
+    }
+
+    /// Converts the value of the `type` column of the `Event` table.
+    pub fn from_db_value(x: i32) -> Self {
+        match x {
+            1 => Self::DeclaredOutput,
+            2 => Self::DeclaredInput,
+            3 => Self::Input,
+            4 => Self::Output,
+            5 => Self::InternalInput,
+            6 => Self::InternalOutput,
+            _ => Self::Unknown,
+        }
+    }
+}
+
 impl std::fmt::Display for EventType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -89,3 +144,18 @@ impl From<mlmd::metadata::EventStep> for EventStep {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventOrderByField {
+    #[default]
+    CreateTime,
+}
+
+impl From<EventOrderByField> for mlmd::requests::EventOrderByField {
+    fn from(x: EventOrderByField) -> Self {
+        match x {
+            EventOrderByField::CreateTime => Self::CreateTime,
+        }
+    }
+}
@@ -15,7 +15,7 @@ impl From<mlmd::metadata::ExecutionType> for ExecutionTypeSummary {
         Self {
             id: x.id.get(),
             name: x.name,
-            properties: x.properties.into_iter().map(|(k, _)| k).collect(),
+            properties: x.properties.into_keys().collect(),
         }
     }
 }
@@ -86,7 +86,9 @@ impl From<(mlmd::metadata::ExecutionType, mlmd::metadata::Execution)> for Execut
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ExecutionState {
     Unknown,
@@ -128,9 +130,10 @@ impl From<mlmd::metadata::ExecutionState> for ExecutionState {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum ExecutionOrderByField {
+    #[default]
     Id,
     Name,
     CreateTime,
@@ -141,12 +144,6 @@ impl ExecutionOrderByField {
     pub const POSSIBLE_VALUES: &'static [&'static str] = &["id", "name", "ctime", "mtime"];
 }
 
-impl Default for ExecutionOrderByField {
-    fn default() -> Self {
-        Self::Id
-    }
-}
-
 impl std::str::FromStr for ExecutionOrderByField {
     type Err = anyhow::Error;
 
@@ -197,7 +194,7 @@ impl ExecutionIdOrName {
                     .type_and_name(type_name, execution_name)
                     .execute()
                     .await?;
-                if let Some(id) = executions.get(0).map(|c| c.id) {
+                if let Some(id) = executions.first().map(|c| c.id) {
                     Ok(id)
                 } else {
                     anyhow::bail!(
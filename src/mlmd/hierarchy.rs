@@ -0,0 +1,199 @@
+//! Parent/child relationships of contexts.
+//!
+//! The `mlmd` crate doesn't expose the `ParentContext` table of ML Metadata,
+//! so this module reads it (and the `Attribution` / `Association` tables) with SQL directly.
+use crate::mlmd::list::BATCH_SIZE;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Debug, Clone, Default)]
+pub struct ContextHierarchy {
+    parents: BTreeMap<i32, BTreeSet<i32>>,
+    children: BTreeMap<i32, BTreeSet<i32>>,
+}
+
+impl ContextHierarchy {
+    /// Loads the parents and the children of `ids`.
+    ///
+    /// Databases created by older versions of ML Metadata don't have the `ParentContext` table,
+    /// in which case the hierarchy is empty.
+    pub async fn load_neighbors(
+        conn: &mut sqlx::AnyConnection,
+        ids: &[i32],
+    ) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+        if !has_parent_context_table(conn).await {
+            return Ok(this);
+        }
+
+        for column in &["context_id", "parent_context_id"] {
+            for (child, parent) in get_relations(conn, column, ids).await? {
+                this.insert(child, parent);
+            }
+        }
+        Ok(this)
+    }
+
+    /// Loads `roots` and their descendants level by level
+    /// until the loaded contexts reach `limit` (the last level is loaded entirely).
+    pub async fn load_descendants(
+        conn: &mut sqlx::AnyConnection,
+        roots: &[i32],
+        limit: usize,
+    ) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+        if !has_parent_context_table(conn).await {
+            return Ok(this);
+        }
+
+        let mut visited = roots.iter().copied().collect::<HashSet<_>>();
+        let mut frontier = visited.iter().copied().collect::<Vec<_>>();
+        while !frontier.is_empty() && visited.len() < limit {
+            let mut next = Vec::new();
+            for (child, parent) in get_relations(conn, "parent_context_id", &frontier).await? {
+                this.insert(child, parent);
+                // ML Metadata rejects cycles, but the table may be modified by other tools.
+                if visited.insert(child) {
+                    next.push(child);
+                }
+            }
+            frontier = next;
+        }
+        Ok(this)
+    }
+
+    fn insert(&mut self, child: i32, parent: i32) {
+        self.parents.entry(child).or_default().insert(parent);
+        self.children.entry(parent).or_default().insert(child);
+    }
+
+    pub fn parents(&self, id: i32) -> impl '_ + Iterator<Item = i32> {
+        self.parents.get(&id).into_iter().flatten().copied()
+    }
+
+    pub fn children(&self, id: i32) -> impl '_ + Iterator<Item = i32> {
+        self.children.get(&id).into_iter().flatten().copied()
+    }
+
+    /// Returns `id` and all its loaded descendants (in breadth-first order).
+    pub fn descendants(&self, id: i32) -> Vec<i32> {
+        let mut visited = HashSet::new();
+        visited.insert(id);
+        let mut result = vec![id];
+        let mut i = 0;
+        while i < result.len() {
+            for child in self.children(result[i]) {
+                if visited.insert(child) {
+                    result.push(child);
+                }
+            }
+            i += 1;
+        }
+        result
+    }
+}
+
+/// Gets the IDs of the contexts that have children but no parent.
+pub async fn get_root_context_ids(conn: &mut sqlx::AnyConnection) -> anyhow::Result<Vec<i32>> {
+    if !has_parent_context_table(conn).await {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<(i32,)> = sqlx::query_as(
+        "SELECT DISTINCT parent_context_id FROM ParentContext \
+         WHERE parent_context_id NOT IN (SELECT context_id FROM ParentContext) \
+         ORDER BY parent_context_id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().map(|x| x.0).collect())
+}
+
+/// Context that an artifact or execution belongs to.
+#[derive(Debug, Clone)]
+pub struct Membership {
+    /// ID of the artifact or execution.
+    pub item_id: i32,
+    pub context_id: i32,
+    pub context_type: String,
+    pub context_name: String,
+}
+
+/// Gets the contexts that any of `artifacts` is attributed to.
+pub async fn get_artifact_memberships(
+    conn: &mut sqlx::AnyConnection,
+    artifacts: &[i32],
+) -> anyhow::Result<Vec<Membership>> {
+    get_memberships(conn, "artifact_id", "Attribution", artifacts).await
+}
+
+/// Gets the contexts that any of `executions` is associated with.
+pub async fn get_execution_memberships(
+    conn: &mut sqlx::AnyConnection,
+    executions: &[i32],
+) -> anyhow::Result<Vec<Membership>> {
+    get_memberships(conn, "execution_id", "Association", executions).await
+}
+
+async fn has_parent_context_table(conn: &mut sqlx::AnyConnection) -> bool {
+    sqlx::query("SELECT 1 FROM ParentContext LIMIT 1")
+        .fetch_optional(&mut *conn)
+        .await
+        .is_ok()
+}
+
+async fn get_memberships(
+    conn: &mut sqlx::AnyConnection,
+    column: &str,
+    table: &str,
+    items: &[i32],
+) -> anyhow::Result<Vec<Membership>> {
+    let mut memberships = Vec::new();
+    for items in items.chunks(BATCH_SIZE) {
+        let sql = format!(
+            "SELECT X.{0}, Y.id, Z.name, Y.name FROM {1} AS X \
+             JOIN Context AS Y ON X.context_id = Y.id \
+             JOIN Type AS Z ON Y.type_id = Z.id \
+             WHERE X.{0} IN ({2})",
+            column,
+            table,
+            vec!["?"; items.len()].join(",")
+        );
+        let mut query = sqlx::query_as(&sql);
+        for id in items {
+            query = query.bind(*id);
+        }
+        let rows: Vec<(i32, i32, String, String)> = query.fetch_all(&mut *conn).await?;
+        memberships.extend(rows.into_iter().map(
+            |(item_id, context_id, context_type, context_name)| Membership {
+                item_id,
+                context_id,
+                context_type,
+                context_name,
+            },
+        ));
+    }
+    Ok(memberships)
+}
+
+/// Gets the `(context_id, parent_context_id)` rows of `ParentContext` whose `column` is in `ids`.
+async fn get_relations(
+    conn: &mut sqlx::AnyConnection,
+    column: &str,
+    ids: &[i32],
+) -> anyhow::Result<Vec<(i32, i32)>> {
+    let mut rows = Vec::new();
+    for ids in ids.chunks(BATCH_SIZE) {
+        let sql = format!(
+            "SELECT context_id, parent_context_id FROM ParentContext WHERE {} IN ({})",
+            column,
+            vec!["?"; ids.len()].join(",")
+        );
+        let mut query = sqlx::query_as(&sql);
+        for id in ids {
+            query = query.bind(*id);
+        }
+        let chunk: Vec<(i32, i32)> = query.fetch_all(&mut *conn).await?;
+        rows.extend(chunk);
+    }
+    Ok(rows)
+}
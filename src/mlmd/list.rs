@@ -0,0 +1,367 @@
+//! Pages of artifacts, executions and contexts selected with SQL.
+//!
+//! The `mlmd` crate only supports offset-based pagination, which gets slower as pages get deeper,
+//! and doesn't break ties of the ordering field, so this module selects the IDs of a page
+//! with SQL directly, resuming right after the last item of the previous page (keyset pagination).
+use crate::hook::ItemType;
+use std::ops::Bound;
+use std::time::Duration;
+
+/// Maximum number of IDs in a single `IN (...)` clause of bind variables.
+pub const BATCH_SIZE: usize = 500;
+
+/// Column to order items by (ties are broken by the item IDs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderColumn {
+    Id,
+    Name,
+    CreateTime,
+    UpdateTime,
+}
+
+impl OrderColumn {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Name => "name",
+            Self::CreateTime => "create_time_since_epoch",
+            Self::UpdateTime => "last_update_time_since_epoch",
+        }
+    }
+}
+
+/// Position right after the last item of a page.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cursor {
+    /// ID of the last item.
+    pub id: i32,
+
+    /// Value of the ordering column of the last item.
+    ///
+    /// This is `None` if the items are ordered by ID or the value is null.
+    pub value: Option<String>,
+}
+
+impl Cursor {
+    pub fn id(id: i32) -> Self {
+        Self { id, value: None }
+    }
+
+    pub fn name(id: i32, name: Option<&str>) -> Self {
+        Self {
+            id,
+            value: name.map(|x| x.to_owned()),
+        }
+    }
+
+    pub fn time(id: i32, time: Duration) -> Self {
+        Self {
+            id,
+            value: Some(time.as_millis().to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "{}.{}", self.id, value)
+        } else {
+            write!(f, "{}", self.id)
+        }
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut tokens = s.splitn(2, '.');
+        let id = tokens.next().expect("unreachable").parse()?;
+        let value = tokens.next().map(|x| x.to_owned());
+        Ok(Self { id, value })
+    }
+}
+
+impl std::convert::TryFrom<String> for Cursor {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(x: Cursor) -> Self {
+        x.to_string()
+    }
+}
+
+/// Keyset of a page relative to an item of an adjacent page.
+#[derive(Debug, Clone, Copy)]
+pub enum Keyset<'a> {
+    /// The page starts right after the item (i.e., the last item of the previous page).
+    After(&'a Cursor),
+
+    /// The page ends right before the item (i.e., the first item of the next page).
+    Before(&'a Cursor),
+}
+
+/// Conditions that items must satisfy.
+#[derive(Debug, Clone)]
+pub struct ItemFilter {
+    pub type_name: Option<String>,
+    pub name: Option<String>,
+    /// Matches the artifacts / executions in any of these contexts.
+    ///
+    /// The IDs are embedded in the SQL as literals, so a large set of contexts
+    /// (e.g., a deep subtree) doesn't run into the limit of bind variables.
+    pub contexts: Option<Vec<i32>>,
+    /// Matches the artifacts / executions in any context of this type.
+    pub context_type: Option<String>,
+    /// Matches the contexts of this artifact.
+    pub artifact: Option<i32>,
+    /// Matches the contexts of this execution.
+    pub execution: Option<i32>,
+    pub update_time: (Bound<Duration>, Bound<Duration>),
+}
+
+impl Default for ItemFilter {
+    fn default() -> Self {
+        Self {
+            type_name: None,
+            name: None,
+            contexts: None,
+            context_type: None,
+            artifact: None,
+            execution: None,
+            update_time: (Bound::Unbounded, Bound::Unbounded),
+        }
+    }
+}
+
+/// Counts the items of `item_type` matching `filter`.
+pub async fn count_items(
+    conn: &mut sqlx::AnyConnection,
+    item_type: ItemType,
+    filter: &ItemFilter,
+) -> anyhow::Result<usize> {
+    let query = SelectQuery::new(item_type, filter, "COUNT(*)");
+    let sql = query.sql();
+    let count: i64 = bind_all(sqlx::query_scalar(&sql), query.args)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(count as usize)
+}
+
+/// Gets the IDs of a page of the items of `item_type` matching `filter`.
+///
+/// The page is located by `keyset` if specified, otherwise it starts after the first `offset` items.
+pub async fn get_item_ids(
+    conn: &mut sqlx::AnyConnection,
+    item_type: ItemType,
+    filter: &ItemFilter,
+    (column, asc): (OrderColumn, bool),
+    keyset: Option<Keyset<'_>>,
+    offset: usize,
+    limit: usize,
+) -> anyhow::Result<Vec<i32>> {
+    let mut query = SelectQuery::new(item_type, filter, "A.id");
+    // A page ending before a cursor is selected in the reverse order, and then reversed back.
+    let (asc, reverse) = match keyset {
+        Some(Keyset::Before(_)) => (!asc, true),
+        _ => (asc, false),
+    };
+    let offset = match keyset {
+        Some(Keyset::After(cursor) | Keyset::Before(cursor)) => {
+            query.after(column, asc, cursor)?;
+            0
+        }
+        None => offset,
+    };
+
+    let order = if asc { "ASC" } else { "DESC" };
+    let mut sql = query.sql();
+    if column == OrderColumn::Id {
+        sql += &format!(" ORDER BY A.id {}", order);
+    } else {
+        sql += &format!(" ORDER BY A.{} {}, A.id {}", column.name(), order, order);
+    }
+    sql += &format!(" LIMIT {} OFFSET {}", limit, offset);
+
+    let mut ids: Vec<i32> = bind_all(sqlx::query_scalar(&sql), query.args)
+        .fetch_all(&mut *conn)
+        .await?;
+    if reverse {
+        ids.reverse();
+    }
+    Ok(ids)
+}
+
+#[derive(Debug)]
+enum Arg {
+    Int(i64),
+    Str(String),
+}
+
+#[derive(Debug)]
+struct SelectQuery {
+    select: String,
+    conditions: Vec<String>,
+    args: Vec<Arg>,
+}
+
+impl SelectQuery {
+    fn new(item_type: ItemType, filter: &ItemFilter, columns: &str) -> Self {
+        let table = match item_type {
+            ItemType::Artifact => "Artifact",
+            ItemType::Execution => "Execution",
+            ItemType::Context => "Context",
+        };
+        let mut this = Self {
+            select: format!("SELECT {} FROM {} AS A", columns, table),
+            conditions: Vec::new(),
+            args: Vec::new(),
+        };
+
+        if let Some(type_name) = &filter.type_name {
+            this.select += " JOIN Type AS T ON A.type_id = T.id";
+            this.conditions.push("T.name = ?".to_owned());
+            this.args.push(Arg::Str(type_name.clone()));
+        }
+        if let Some(name) = &filter.name {
+            this.conditions.push("A.name = ?".to_owned());
+            this.args.push(Arg::Str(name.clone()));
+        }
+        let membership = match item_type {
+            ItemType::Artifact => Some(("Attribution", "artifact_id")),
+            ItemType::Execution => Some(("Association", "execution_id")),
+            ItemType::Context => None,
+        };
+        if let Some((table, column)) = membership {
+            if let Some(contexts) = &filter.contexts {
+                let contexts = contexts.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+                this.conditions.push(if contexts.is_empty() {
+                    "1 = 0".to_owned()
+                } else {
+                    format!(
+                        "A.id IN (SELECT {} FROM {} WHERE context_id IN ({}))",
+                        column,
+                        table,
+                        contexts.join(",")
+                    )
+                });
+            }
+            if let Some(context_type) = &filter.context_type {
+                this.conditions.push(format!(
+                    "A.id IN (SELECT X.{} FROM {} AS X \
+                     JOIN Context AS Y ON X.context_id = Y.id \
+                     JOIN Type AS Z ON Y.type_id = Z.id WHERE Z.name = ?)",
+                    column, table
+                ));
+                this.args.push(Arg::Str(context_type.clone()));
+            }
+        } else {
+            let memberships = [
+                ("Attribution", "artifact_id", filter.artifact),
+                ("Association", "execution_id", filter.execution),
+            ];
+            for (table, key, id) in memberships {
+                if let Some(id) = id {
+                    this.conditions.push(format!(
+                        "A.id IN (SELECT context_id FROM {} WHERE {} = ?)",
+                        table, key
+                    ));
+                    this.args.push(Arg::Int(i64::from(id)));
+                }
+            }
+        }
+
+        let column = OrderColumn::UpdateTime.name();
+        match filter.update_time.0 {
+            Bound::Included(t) => this.compare(column, ">=", t),
+            Bound::Excluded(t) => this.compare(column, ">", t),
+            Bound::Unbounded => {}
+        }
+        match filter.update_time.1 {
+            Bound::Included(t) => this.compare(column, "<=", t),
+            Bound::Excluded(t) => this.compare(column, "<", t),
+            Bound::Unbounded => {}
+        }
+        this
+    }
+
+    fn compare(&mut self, column: &str, op: &str, time: Duration) {
+        self.conditions.push(format!("A.{} {} ?", column, op));
+        self.args.push(Arg::Int(time.as_millis() as i64));
+    }
+
+    /// Narrows the items to the ones after `cursor` in the order of `column`.
+    fn after(&mut self, column: OrderColumn, asc: bool, cursor: &Cursor) -> anyhow::Result<()> {
+        let gt = if asc { ">" } else { "<" };
+        let id = Arg::Int(i64::from(cursor.id));
+        match (column, &cursor.value) {
+            (OrderColumn::Id, _) => {
+                self.conditions.push(format!("A.id {} ?", gt));
+                self.args.push(id);
+            }
+            (OrderColumn::CreateTime | OrderColumn::UpdateTime, value) => {
+                let value = value
+                    .as_ref()
+                    .and_then(|x| x.parse::<i64>().ok())
+                    .ok_or_else(|| anyhow::anyhow!("invalid cursor: {}", cursor))?;
+                let column = column.name();
+                self.conditions.push(format!(
+                    "(A.{c} {} ? OR (A.{c} = ? AND A.id {} ?))",
+                    gt,
+                    gt,
+                    c = column
+                ));
+                self.args.extend(vec![Arg::Int(value), Arg::Int(value), id]);
+            }
+            // Null names come first in the ascending order of both SQLite and MySQL.
+            (OrderColumn::Name, None) if asc => {
+                self.conditions
+                    .push("((A.name IS NULL AND A.id > ?) OR A.name IS NOT NULL)".to_owned());
+                self.args.push(id);
+            }
+            (OrderColumn::Name, None) => {
+                self.conditions
+                    .push("(A.name IS NULL AND A.id < ?)".to_owned());
+                self.args.push(id);
+            }
+            (OrderColumn::Name, Some(name)) => {
+                let nulls = if asc { "" } else { " OR A.name IS NULL" };
+                self.conditions.push(format!(
+                    "(A.name {} ? OR (A.name = ? AND A.id {} ?){})",
+                    gt, gt, nulls
+                ));
+                self.args
+                    .extend(vec![Arg::Str(name.clone()), Arg::Str(name.clone()), id]);
+            }
+        }
+        Ok(())
+    }
+
+    fn sql(&self) -> String {
+        if self.conditions.is_empty() {
+            self.select.clone()
+        } else {
+            format!("{} WHERE {}", self.select, self.conditions.join(" AND "))
+        }
+    }
+}
+
+fn bind_all<'q, O>(
+    mut query: sqlx::query::QueryScalar<'q, sqlx::Any, O, sqlx::any::AnyArguments<'q>>,
+    args: Vec<Arg>,
+) -> sqlx::query::QueryScalar<'q, sqlx::Any, O, sqlx::any::AnyArguments<'q>> {
+    for arg in args {
+        query = match arg {
+            Arg::Int(x) => query.bind(x),
+            Arg::Str(x) => query.bind(x),
+        };
+    }
+    query
+}
@@ -0,0 +1,151 @@
+//! Counts of artifacts, executions and contexts aggregated with SQL.
+//!
+//! The `mlmd` crate can only count the items matching a single condition,
+//! which would take a query per day, per type or per context, so this module groups them with SQL directly.
+use crate::hook::ItemType;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const UPDATE_TIME: &str = "last_update_time_since_epoch";
+
+/// Number of the executions and artifacts in a context.
+#[derive(Debug, Clone)]
+pub struct ContextSize {
+    pub context_id: i32,
+    pub executions: usize,
+    pub artifacts: usize,
+}
+
+/// Counts the items of `item_type` updated in each period between adjacent `boundaries`.
+///
+/// `boundaries` must be in ascending order, and the `i`-th count is of `boundaries[i]..boundaries[i + 1]`.
+pub async fn count_by_period(
+    conn: &mut sqlx::AnyConnection,
+    item_type: ItemType,
+    boundaries: &[Duration],
+) -> anyhow::Result<Vec<usize>> {
+    let mut counts = vec![0; boundaries.len().saturating_sub(1)];
+    if counts.is_empty() {
+        return Ok(counts);
+    }
+
+    let ms = |t: &Duration| t.as_millis().to_string();
+    let cases = boundaries[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("WHEN {} < {} THEN {}", UPDATE_TIME, ms(t), i))
+        .collect::<Vec<_>>();
+    let sql = format!(
+        "SELECT CASE {} END AS period, COUNT(*) FROM {} WHERE {} >= {} AND {} < {} GROUP BY period",
+        cases.join(" "),
+        table(item_type),
+        UPDATE_TIME,
+        ms(&boundaries[0]),
+        UPDATE_TIME,
+        ms(&boundaries[boundaries.len() - 1])
+    );
+    let rows: Vec<(i64, i64)> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+    for (period, count) in rows {
+        if let Some(x) = counts.get_mut(period as usize) {
+            *x = count as usize;
+        }
+    }
+    Ok(counts)
+}
+
+/// Counts the items of `item_type` (updated at or after `since` if specified) per type ID.
+pub async fn count_by_type(
+    conn: &mut sqlx::AnyConnection,
+    item_type: ItemType,
+    since: Option<Duration>,
+) -> anyhow::Result<HashMap<i32, usize>> {
+    let mut sql = format!("SELECT type_id, COUNT(*) FROM {}", table(item_type));
+    if let Some(since) = since {
+        sql += &format!(" WHERE {} >= {}", UPDATE_TIME, since.as_millis());
+    }
+    sql += " GROUP BY type_id";
+    let rows: Vec<(i32, i64)> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(type_id, count)| (type_id, count as usize))
+        .collect())
+}
+
+/// Counts the artifacts or executions (updated at or after `since`) per state.
+///
+/// The keys are the values of the state enums of ML Metadata (a null state is counted as `0`, i.e., `UNKNOWN`).
+pub async fn count_by_state(
+    conn: &mut sqlx::AnyConnection,
+    item_type: ItemType,
+    since: Duration,
+) -> anyhow::Result<HashMap<i32, usize>> {
+    let column = match item_type {
+        ItemType::Artifact => "state",
+        ItemType::Execution => "last_known_state",
+        ItemType::Context => anyhow::bail!("contexts have no state"),
+    };
+    let sql = format!(
+        "SELECT COALESCE({0}, 0) AS s, COUNT(*) FROM {1} WHERE {2} >= {3} GROUP BY s",
+        column,
+        table(item_type),
+        UPDATE_TIME,
+        since.as_millis()
+    );
+    let rows: Vec<(i32, i64)> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(state, count)| (state, count as usize))
+        .collect())
+}
+
+/// Gets the `limit` contexts that have the most executions and artifacts in descending order.
+pub async fn get_largest_contexts(
+    conn: &mut sqlx::AnyConnection,
+    limit: usize,
+) -> anyhow::Result<Vec<ContextSize>> {
+    let sql = format!(
+        "SELECT context_id FROM \
+         (SELECT context_id FROM Association UNION ALL SELECT context_id FROM Attribution) AS X \
+         GROUP BY context_id ORDER BY COUNT(*) DESC, context_id LIMIT {}",
+        limit
+    );
+    let ids: Vec<i32> = sqlx::query_scalar(&sql).fetch_all(&mut *conn).await?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ids_sql = ids
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut counts = Vec::new();
+    for table in &["Association", "Attribution"] {
+        let sql = format!(
+            "SELECT context_id, COUNT(*) FROM {} WHERE context_id IN ({}) GROUP BY context_id",
+            table, ids_sql
+        );
+        let rows: Vec<(i32, i64)> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+        counts.push(
+            rows.into_iter()
+                .map(|(id, count)| (id, count as usize))
+                .collect::<HashMap<_, _>>(),
+        );
+    }
+    Ok(ids
+        .into_iter()
+        .map(|context_id| ContextSize {
+            context_id,
+            executions: counts[0].get(&context_id).copied().unwrap_or(0),
+            artifacts: counts[1].get(&context_id).copied().unwrap_or(0),
+        })
+        .collect())
+}
+
+fn table(item_type: ItemType) -> &'static str {
+    match item_type {
+        ItemType::Artifact => "Artifact",
+        ItemType::Execution => "Execution",
+        ItemType::Context => "Context",
+    }
+}
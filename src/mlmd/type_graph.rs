@@ -0,0 +1,37 @@
+//! Event counts aggregated by the types of the connected artifacts and executions.
+//!
+//! Aggregating all the events through the `mlmd` crate would fetch every event and node,
+//! so this module reads the `Event` table with SQL directly.
+use crate::mlmd::event::EventType;
+
+#[derive(Debug, Clone)]
+pub struct TypeEventCount {
+    pub artifact_type_id: i32,
+    pub execution_type_id: i32,
+    pub event_type: EventType,
+    pub count: i64,
+}
+
+pub async fn get_type_event_counts(
+    conn: &mut sqlx::AnyConnection,
+) -> anyhow::Result<Vec<TypeEventCount>> {
+    let rows: Vec<(i32, i32, i32, i64)> = sqlx::query_as(
+        "SELECT a.type_id, x.type_id, e.type, COUNT(*) FROM Event AS e \
+         JOIN Artifact AS a ON e.artifact_id = a.id \
+         JOIN Execution AS x ON e.execution_id = x.id \
+         GROUP BY a.type_id, x.type_id, e.type",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(artifact_type_id, execution_type_id, event_type, count)| TypeEventCount {
+                artifact_type_id,
+                execution_type_id,
+                event_type: EventType::from_db_value(event_type),
+                count,
+            },
+        )
+        .collect())
+}
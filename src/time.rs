@@ -1,3 +1,5 @@
+use chrono::{FixedOffset, NaiveDateTime, Offset as _, TimeZone as _};
+use std::ops::Bound;
 use std::time::{Duration, SystemTime};
 
 pub type DateTime = chrono::DateTime<chrono::Local>;
@@ -5,3 +7,242 @@ pub type DateTime = chrono::DateTime<chrono::Local>;
 pub fn duration_to_datetime(d: Duration) -> DateTime {
     DateTime::from(SystemTime::UNIX_EPOCH + d)
 }
+
+pub fn datetime_to_duration(t: DateTime) -> Duration {
+    Duration::from_millis(t.timestamp_millis() as u64)
+}
+
+/// Converts an optional date-time range into the bounds of durations since the UNIX epoch.
+pub fn datetime_range(
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+) -> (Bound<Duration>, Bound<Duration>) {
+    (
+        start.map_or(Bound::Unbounded, |t| {
+            Bound::Included(datetime_to_duration(t))
+        }),
+        end.map_or(Bound::Unbounded, |t| {
+            Bound::Excluded(datetime_to_duration(t))
+        }),
+    )
+}
+
+/// Period ending now, such as `24h` (used by the `mtime-last` query parameter).
+///
+/// The supported units are `m` (minutes), `h` (hours) and `d` (days).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Period {
+    amount: u32,
+    unit: char,
+}
+
+impl Period {
+    pub fn duration(self) -> chrono::Duration {
+        let amount = i64::from(self.amount);
+        match self.unit {
+            'm' => chrono::Duration::minutes(amount),
+            'h' => chrono::Duration::hours(amount),
+            _ => chrono::Duration::days(amount),
+        }
+    }
+
+    /// Returns the beginning of this period as of now (`None` if it's out of the range of date-times).
+    pub fn start(self) -> Option<DateTime> {
+        chrono::Local::now().checked_sub_signed(self.duration())
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit)
+    }
+}
+
+impl std::str::FromStr for Period {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let unit = s
+            .chars()
+            .last()
+            .filter(|c| matches!(c, 'm' | 'h' | 'd'))
+            .ok_or_else(|| anyhow::anyhow!("invalid period (expected e.g. `24h`): {:?}", s))?;
+        let amount = s[..s.len() - 1].parse()?;
+        Ok(Self { amount, unit })
+    }
+}
+
+impl std::convert::TryFrom<String> for Period {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Period> for String {
+    fn from(x: Period) -> Self {
+        x.to_string()
+    }
+}
+
+/// Time zone used to display and to input date-times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TimeZone {
+    /// The local time zone of the server.
+    #[default]
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+
+    /// IANA time zone (e.g., `Asia/Tokyo`), whose offset depends on the date-time because of DST.
+    Named(chrono_tz::Tz),
+}
+
+impl TimeZone {
+    pub fn convert(self, t: DateTime) -> chrono::DateTime<FixedOffset> {
+        t.with_timezone(&self.offset_from_utc(t.naive_utc()))
+    }
+
+    /// Interprets a date-time without an offset as one in this time zone.
+    ///
+    /// The offset is determined for the date-time itself (not for now), so that it's correct across DST changes.
+    /// Returns `None` if the date-time doesn't exist in this time zone (e.g., it's skipped by a DST change).
+    pub fn from_local_datetime(self, t: NaiveDateTime) -> Option<DateTime> {
+        match self {
+            Self::Local => chrono::Local.from_local_datetime(&t).earliest(),
+            Self::Utc => Some(DateTime::from(chrono::Utc.from_utc_datetime(&t))),
+            Self::Fixed(offset) => offset.from_local_datetime(&t).single().map(DateTime::from),
+            Self::Named(tz) => tz
+                .from_local_datetime(&t)
+                .earliest()
+                .map(|t| t.with_timezone(&chrono::Local)),
+        }
+    }
+
+    /// Returns the beginning of today in this time zone.
+    pub fn today(self) -> DateTime {
+        let date = self.convert(chrono::Local::now()).date().naive_local();
+        self.from_local_datetime(date.and_hms(0, 0, 0))
+            // Midnight is skipped by DST changes in a few time zones.
+            .or_else(|| self.from_local_datetime(date.and_hms(1, 0, 0)))
+            .expect("unreachable")
+    }
+
+    fn offset_from_utc(self, t: NaiveDateTime) -> FixedOffset {
+        match self {
+            Self::Local => chrono::Local.offset_from_utc_datetime(&t),
+            Self::Utc => FixedOffset::east(0),
+            Self::Fixed(offset) => offset,
+            Self::Named(tz) => tz.offset_from_utc_datetime(&t).fix(),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Utc => write!(f, "utc"),
+            Self::Fixed(offset) => {
+                let seconds = offset.local_minus_utc();
+                write!(
+                    f,
+                    "{}{:02}:{:02}",
+                    if seconds < 0 { '-' } else { '+' },
+                    seconds.abs() / 3600,
+                    seconds.abs() % 3600 / 60
+                )
+            }
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl std::str::FromStr for TimeZone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        // A leading space is accepted as `+` because an unescaped `+` in a query string is decoded as a space.
+        match s.trim_start_matches(' ') {
+            "local" => Ok(Self::Local),
+            "utc" | "UTC" | "Z" => Ok(Self::Utc),
+            name if !name.starts_with(|c: char| c == '+' || c == '-' || c.is_ascii_digit()) => name
+                .parse()
+                .map(Self::Named)
+                .map_err(|_| anyhow::anyhow!("invalid time zone: {:?}", s)),
+            offset => {
+                let (sign, hhmm) = if let Some(x) = offset.strip_prefix('-') {
+                    (-1, x)
+                } else {
+                    (1, offset.strip_prefix('+').unwrap_or(offset))
+                };
+                // Only digits are accepted because `u32::from_str` also accepts a (second) leading `+`.
+                let parse_digits = |x: &str| -> anyhow::Result<u32> {
+                    anyhow::ensure!(
+                        !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit()),
+                        "invalid time zone: {:?}",
+                        s
+                    );
+                    Ok(x.parse()?)
+                };
+                let mut tokens = hhmm.splitn(2, ':');
+                let hours = parse_digits(tokens.next().expect("unreachable"))?;
+                let minutes = parse_digits(tokens.next().unwrap_or("0"))?;
+                anyhow::ensure!(hours <= 14 && minutes < 60, "invalid time zone: {:?}", s);
+                let seconds = (hours * 3600 + minutes * 60) as i32;
+                FixedOffset::east_opt(sign * seconds)
+                    .map(Self::Fixed)
+                    .ok_or_else(|| anyhow::anyhow!("invalid time zone: {:?}", s))
+            }
+        }
+    }
+}
+
+impl std::convert::TryFrom<String> for TimeZone {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<TimeZone> for String {
+    fn from(x: TimeZone) -> Self {
+        x.to_string()
+    }
+}
+
+/// Parses the value of a date-time query parameter (e.g., `mtime-start`).
+///
+/// The value is either an RFC 3339 date-time (e.g., `2021-01-02T03:04:05+09:00`),
+/// or a date-time without an offset followed by the time zone to interpret it in
+/// (e.g., `2021-01-02T03:04:05[local]`), which is what the date-time inputs of the pages send.
+pub fn parse_datetime_param(s: &str) -> anyhow::Result<DateTime> {
+    if let Some(x) = s.strip_suffix(']') {
+        let (t, tz) = x
+            .rsplit_once('[')
+            .ok_or_else(|| anyhow::anyhow!("invalid date-time: {:?}", s))?;
+        let tz: TimeZone = tz.parse()?;
+        let t = NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S%.f")?;
+        tz.from_local_datetime(t)
+            .ok_or_else(|| anyhow::anyhow!("nonexistent date-time in the time zone: {:?}", s))
+    } else {
+        Ok(s.parse::<chrono::DateTime<FixedOffset>>()?
+            .with_timezone(&chrono::Local))
+    }
+}
+
+/// Deserializes an optional date-time query parameter (see [`parse_datetime_param`]).
+pub fn deserialize_datetime_param<'de, D>(deserializer: D) -> Result<Option<DateTime>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize as _;
+
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_datetime_param(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
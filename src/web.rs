@@ -1,15 +1,31 @@
+use crate::graph::GraphStyle;
 use crate::hook::HookRunner;
-use actix_web::{App, HttpServer};
+use crate::time::TimeZone;
+use actix_web::{App, HttpRequest, HttpServer};
+use openssl::ssl::SslAcceptorBuilder;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod export;
 pub mod handlers;
 pub mod link;
+pub mod pager;
+pub mod query;
 pub mod response;
+pub mod time_range;
+pub mod type_stats;
+pub mod views;
+
+pub const TIME_ZONE_COOKIE: &str = "weih-tz";
 
 #[derive(Debug, Clone)]
 pub struct Config {
     mlmd_db: Arc<String>,
     hook_runner: Arc<HookRunner>,
+    time_zone: TimeZone,
+    plotly_js: Option<Arc<String>>,
+    views: Arc<self::views::ViewStore>,
+    graph_style: Arc<GraphStyle>,
 }
 
 impl Config {
@@ -19,28 +35,79 @@ impl Config {
             .map_err(actix_web::error::ErrorInternalServerError)?;
         Ok(store)
     }
+
+    /// Connects to the database of the metadata store directly (for queries `mlmd` lacks).
+    pub async fn connect_database(&self) -> actix_web::Result<sqlx::AnyConnection> {
+        crate::mlmd::connect(&self.mlmd_db)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)
+    }
+
+    /// Returns the content of the plotly.js that overrides the bundled one, if any.
+    pub fn plotly_js(&self) -> Option<&str> {
+        self.plotly_js.as_ref().map(|x| x.as_str())
+    }
+
+    pub fn views(&self) -> &self::views::ViewStore {
+        &self.views
+    }
+
+    pub fn graph_style(&self) -> &GraphStyle {
+        &self.graph_style
+    }
+
+    /// Returns the time zone for the request.
+    ///
+    /// The `tz` query parameter takes precedence over the `weih-tz` cookie,
+    /// and the server default is used if neither is specified.
+    pub fn time_zone(&self, req: &HttpRequest) -> TimeZone {
+        actix_web::web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()
+            .and_then(|q| q.get("tz").and_then(|tz| tz.parse().ok()))
+            .or_else(|| {
+                req.cookie(TIME_ZONE_COOKIE)
+                    .and_then(|c| c.value().parse().ok())
+            })
+            .unwrap_or(self.time_zone)
+    }
 }
 
-pub async fn http_server_run(
-    bind_addr: std::net::SocketAddr,
-    mlmd_db: String,
-    hook_runner: HookRunner,
-) -> anyhow::Result<()> {
+/// Options of the HTTP server.
+pub struct HttpServerOpt {
+    pub bind_addr: std::net::SocketAddr,
+    pub tls: Option<SslAcceptorBuilder>,
+    pub mlmd_db: String,
+    pub hook_runner: HookRunner,
+    pub time_zone: TimeZone,
+    pub plotly_js: Option<String>,
+    pub views: self::views::ViewStore,
+    pub graph_style: GraphStyle,
+}
+
+pub async fn http_server_run(opt: HttpServerOpt) -> anyhow::Result<()> {
     let config = Config {
-        mlmd_db: Arc::new(mlmd_db.to_owned()),
-        hook_runner: Arc::new(hook_runner),
+        mlmd_db: Arc::new(opt.mlmd_db),
+        hook_runner: Arc::new(opt.hook_runner),
+        time_zone: opt.time_zone,
+        plotly_js: opt.plotly_js.map(Arc::new),
+        views: Arc::new(opt.views),
+        graph_style: Arc::new(opt.graph_style),
     };
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .app_data(actix_web::web::Data::new(config.clone()))
             .service(self::handlers::index::get_index)
             .service(self::handlers::css::get_github_markdown_css)
+            .service(self::handlers::js::get_plotly_js)
+            .service(self::handlers::js::get_lineage_explorer_js)
             .service(self::handlers::artifact_types::get_artifact_type_summaries)
             .service(self::handlers::artifact_types::get_artifact_type_detail)
             .service(self::handlers::artifacts::get_artifacts)
             .service(self::handlers::artifacts::get_artifact)
             .service(self::handlers::artifacts::get_artifact_content)
             .service(self::handlers::artifacts::get_artifact_graph)
+            .service(self::handlers::artifacts::get_artifact_upstream)
+            .service(self::handlers::artifacts::get_artifact_downstream)
             .service(self::handlers::execution_types::get_execution_type_summaries)
             .service(self::handlers::execution_types::get_execution_type_detail)
             .service(self::handlers::executions::get_executions)
@@ -50,14 +117,36 @@ pub async fn http_server_run(
             .service(self::handlers::context_types::get_context_type_summaries)
             .service(self::handlers::context_types::get_context_type_detail)
             .service(self::handlers::contexts::get_contexts)
+            .service(self::handlers::contexts::get_context_tree)
             .service(self::handlers::contexts::get_context)
             .service(self::handlers::contexts::get_context_content)
             .service(self::handlers::events::get_events)
+            .service(self::handlers::lineage::get_lineage_neighbors)
+            .service(self::handlers::lineage::get_lineage_explorer)
+            .service(self::handlers::diff::diff_executions)
+            .service(self::handlers::diff::diff_contexts)
+            .service(self::handlers::export::export_artifacts)
+            .service(self::handlers::export::export_executions)
+            .service(self::handlers::export::export_contexts)
+            .service(self::handlers::export::export_events)
             .service(self::handlers::plot::plot_histogram)
             .service(self::handlers::plot::plot_scatter)
-    })
-    .bind(bind_addr)?
-    .run()
-    .await?;
+            .service(self::handlers::plot::plot_line)
+            .service(self::handlers::plot::plot_distribution)
+            .service(self::handlers::plot::plot_parallel)
+            .service(self::handlers::timeline::get_timeline)
+            .service(self::handlers::types::get_type_graph)
+            .service(self::handlers::views::get_views)
+            .service(self::handlers::views::new_view)
+            .service(self::handlers::views::save_view)
+            .service(self::handlers::views::delete_view)
+            .service(self::handlers::views::get_view)
+    });
+    let server = if let Some(tls) = opt.tls {
+        server.bind_openssl(opt.bind_addr, tls)?
+    } else {
+        server.bind(opt.bind_addr)?
+    };
+    server.run().await?;
     Ok(())
 }
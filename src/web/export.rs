@@ -0,0 +1,342 @@
+use crate::web::query::{self, ListQuery, OrderByField};
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use futures::{Stream, StreamExt as _};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead as _, BufReader, BufWriter, Read as _, Seek as _, SeekFrom, Write as _};
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Number of items fetched from the metadata store at once while exporting (i.e., the rows of a page).
+pub const BATCH_SIZE: usize = crate::mlmd::list::BATCH_SIZE;
+
+/// Map-typed fields that are flattened into `${FIELD}.${KEY}` columns.
+const FLATTENED_FIELDS: &[&str] = &["properties", "custom_properties"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    const ALL: &'static [Self] = &[Self::Csv, Self::Jsonl, Self::Parquet];
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Jsonl => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Csv => write!(f, "CSV"),
+            Self::Jsonl => write!(f, "JSONL"),
+            Self::Parquet => write!(f, "Parquet"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportQuery {
+    pub format: ExportFormat,
+}
+
+/// Makes the markdown of the links to export all the items matching `query`.
+pub fn export_links_md<F: OrderByField>(query: &ListQuery<F>) -> String {
+    let qs = query.all().to_qs();
+    let links = ExportFormat::ALL
+        .iter()
+        .map(|&format| {
+            format!(
+                "[{}](/export{}?{})",
+                format,
+                F::PATH,
+                query::join_qs(&[&query::to_qs(&ExportQuery { format }), &qs])
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("Export: {}", links.join(" "))
+}
+
+/// Makes a response to download the items of `pages` as a `${name}.${extension}` file.
+///
+/// JSON Lines keeps the structure of the items as is,
+/// while CSV and Parquet flatten the properties into columns.
+///
+/// The body is streamed page by page. CSV and Parquet need all the columns (and their types) beforehand,
+/// so their rows are spooled to a temporary file first instead of being kept in memory,
+/// and each page of the rows becomes a row group of Parquet.
+pub async fn download<T, S>(
+    format: ExportFormat,
+    name: &str,
+    pages: S,
+) -> anyhow::Result<HttpResponse>
+where
+    T: serde::Serialize + 'static,
+    S: Stream<Item = anyhow::Result<Vec<T>>> + 'static,
+{
+    let body: Pin<Box<dyn Stream<Item = anyhow::Result<Vec<u8>>>>> = match format {
+        ExportFormat::Jsonl => Box::pin(pages.map(|items| to_json_lines(&items?))),
+        ExportFormat::Csv => Box::pin(futures::stream::iter(to_csv(Spool::new(pages).await?))),
+        ExportFormat::Parquet => Box::pin(futures::stream::iter(read_chunks(to_parquet(
+            Spool::new(pages).await?,
+        )?))),
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .append_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+        ))
+        .streaming(body.map(|chunk| {
+            chunk
+                .map(Bytes::from)
+                .map_err(actix_web::error::ErrorInternalServerError)
+        })))
+}
+
+/// Flattened rows spooled to a temporary file (as JSON Lines) with their columns.
+#[derive(Debug)]
+struct Spool {
+    columns: Vec<String>,
+    data_types: HashMap<String, DataType>,
+    rows: BufReader<File>,
+}
+
+impl Spool {
+    async fn new<T, S>(pages: S) -> anyhow::Result<Self>
+    where
+        T: serde::Serialize,
+        S: Stream<Item = anyhow::Result<Vec<T>>>,
+    {
+        let mut columns = Vec::new();
+        let mut flattened_columns = BTreeSet::new();
+        let mut data_types = HashMap::new();
+        let mut file = BufWriter::new(tempfile::tempfile()?);
+        futures::pin_mut!(pages);
+        while let Some(items) = pages.next().await {
+            for item in items? {
+                let mut row = BTreeMap::new();
+                let object = match serde_json::to_value(item)? {
+                    serde_json::Value::Object(x) => x,
+                    x => anyhow::bail!("not an object: {}", x),
+                };
+                for (k, v) in object {
+                    match v {
+                        serde_json::Value::Object(map)
+                            if FLATTENED_FIELDS.contains(&k.as_str()) =>
+                        {
+                            for (name, v) in map {
+                                let column = format!("{}.{}", k, name);
+                                flattened_columns.insert(column.clone());
+                                row.insert(column, v);
+                            }
+                        }
+                        v => {
+                            if !columns.contains(&k) {
+                                columns.push(k.clone());
+                            }
+                            row.insert(k, v);
+                        }
+                    }
+                }
+                for (column, v) in &row {
+                    if !v.is_null() {
+                        let ty = merge_data_type(data_types.get(column), v);
+                        data_types.insert(column.clone(), ty);
+                    }
+                }
+                serde_json::to_writer(&mut file, &row)?;
+                file.write_all(b"\n")?;
+            }
+        }
+        columns.extend(flattened_columns);
+
+        let mut file = file.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self {
+            columns,
+            data_types,
+            rows: BufReader::new(file),
+        })
+    }
+
+    /// Returns the type of `column` (strings if the column has values of conflicting types or no values).
+    fn data_type(&self, column: &str) -> DataType {
+        self.data_types
+            .get(column)
+            .cloned()
+            .unwrap_or(DataType::Utf8)
+    }
+
+    /// Reads the next (at most) `BATCH_SIZE` rows.
+    fn next_rows(&mut self) -> anyhow::Result<Vec<BTreeMap<String, serde_json::Value>>> {
+        let mut rows = Vec::new();
+        let mut line = String::new();
+        while rows.len() < BATCH_SIZE {
+            line.clear();
+            if self.rows.read_line(&mut line)? == 0 {
+                break;
+            }
+            rows.push(serde_json::from_str(&line)?);
+        }
+        Ok(rows)
+    }
+}
+
+/// Returns the type of the values of a column that has `ty` so far and `v` next.
+fn merge_data_type(ty: Option<&DataType>, v: &serde_json::Value) -> DataType {
+    let t = match v {
+        serde_json::Value::Bool(_) => DataType::Boolean,
+        serde_json::Value::Number(n) if n.is_i64() => DataType::Int64,
+        serde_json::Value::Number(_) => DataType::Float64,
+        _ => return DataType::Utf8,
+    };
+    match (ty, t) {
+        (None, t) => t,
+        (Some(DataType::Int64), DataType::Float64) | (Some(DataType::Float64), DataType::Int64) => {
+            DataType::Float64
+        }
+        (Some(a), b) if *a == b => b,
+        _ => DataType::Utf8,
+    }
+}
+
+fn cell_to_string(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        v => Some(v.to_string()),
+    }
+}
+
+/// Converts the rows of `spool` into CSV chunks, one per `BATCH_SIZE` rows (following the header).
+fn to_csv(mut spool: Spool) -> impl Iterator<Item = anyhow::Result<Vec<u8>>> {
+    let mut header = Some(spool.columns.clone());
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let chunk = (|| {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            if let Some(header) = header.take() {
+                writer.write_record(&header)?;
+            }
+            let rows = spool.next_rows()?;
+            done = rows.is_empty();
+            for row in &rows {
+                writer.write_record(spool.columns.iter().map(|c| {
+                    row.get(c)
+                        .and_then(cell_to_string)
+                        .unwrap_or_else(String::new)
+                }))?;
+            }
+            Ok(writer.into_inner()?)
+        })();
+        done |= chunk.is_err();
+        Some(chunk)
+    })
+}
+
+fn to_json_lines<T: serde::Serialize>(items: &[T]) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut buf, item)?;
+        buf.push(b'\n');
+    }
+    Ok(buf)
+}
+
+/// Writes the rows of `spool` into a temporary Parquet file with a row group per `BATCH_SIZE` rows.
+fn to_parquet(mut spool: Spool) -> anyhow::Result<File> {
+    let fields = spool
+        .columns
+        .iter()
+        .map(|column| Field::new(column, spool.data_type(column), true))
+        .collect::<Vec<_>>();
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut file = tempfile::tempfile()?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file.try_clone()?, schema.clone(), None)?;
+    loop {
+        let rows = spool.next_rows()?;
+        if rows.is_empty() {
+            break;
+        }
+        let mut arrays: Vec<ArrayRef> = Vec::new();
+        for column in &spool.columns {
+            let cells = rows.iter().map(|row| row.get(column));
+            let array: ArrayRef = match spool.data_type(column) {
+                DataType::Boolean => Arc::new(BooleanArray::from(
+                    cells
+                        .map(|v| v.and_then(|v| v.as_bool()))
+                        .collect::<Vec<_>>(),
+                )),
+                DataType::Int64 => Arc::new(Int64Array::from(
+                    cells
+                        .map(|v| v.and_then(|v| v.as_i64()))
+                        .collect::<Vec<_>>(),
+                )),
+                DataType::Float64 => Arc::new(Float64Array::from(
+                    cells
+                        .map(|v| v.and_then(|v| v.as_f64()))
+                        .collect::<Vec<_>>(),
+                )),
+                _ => Arc::new(
+                    cells
+                        .map(|v| v.and_then(cell_to_string))
+                        .collect::<StringArray>(),
+                ),
+            };
+            arrays.push(array);
+        }
+        writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+    }
+    writer.close()?;
+
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+/// Reads `file` in chunks.
+fn read_chunks(mut file: File) -> impl Iterator<Item = anyhow::Result<Vec<u8>>> {
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let mut buf = vec![0; 64 * 1024];
+        match file.read(&mut buf) {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some(Ok(buf))
+            }
+            Err(e) => {
+                done = true;
+                Some(Err(e.into()))
+            }
+        }
+    })
+}
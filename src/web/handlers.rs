@@ -3,8 +3,15 @@ pub mod artifacts;
 pub mod context_types;
 pub mod contexts;
 pub mod css;
+pub mod diff;
 pub mod events;
 pub mod execution_types;
 pub mod executions;
+pub mod export;
 pub mod index;
+pub mod js;
+pub mod lineage;
 pub mod plot;
+pub mod timeline;
+pub mod types;
+pub mod views;
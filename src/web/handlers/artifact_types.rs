@@ -1,7 +1,13 @@
-use crate::mlmd::artifact::{ArtifactTypeDetail, ArtifactTypeSummary};
+use crate::graph::NodeId;
+use crate::mlmd::artifact::{
+    Artifact, ArtifactOrderByField, ArtifactTypeDetail, ArtifactTypeSummary,
+};
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
 use crate::web::link::Link;
+use crate::web::type_stats::{self, TypeStats};
 use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 
 #[get("/artifact_types/")]
 async fn get_artifact_type_summaries(config: web::Data<Config>) -> actix_web::Result<HttpResponse> {
@@ -15,6 +21,8 @@ async fn get_artifact_type_summaries(config: web::Data<Config>) -> actix_web::Re
     let mut md = concat!(
         "# Artifact Types\n",
         "\n",
+        "[Type Graph](/types/graph)\n",
+        "\n",
         "| id | name | properties |\n",
         "|----|------|------------|\n"
     )
@@ -37,9 +45,12 @@ async fn get_artifact_type_summaries(config: web::Data<Config>) -> actix_web::Re
 async fn get_artifact_type_detail(
     config: web::Data<Config>,
     path: web::Path<(i32,)>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let id = path.0;
     let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
 
     let types = store
         .get_artifact_types()
@@ -59,12 +70,84 @@ async fn get_artifact_type_detail(
 
     md += &format!("- ID: {}\n", ty.id);
     md += &format!("- Name: {}\n", ty.name);
-    md += &format!("- Properties:\n");
+    md += "- Properties:\n";
 
     for (k, v) in &ty.properties {
         md += &format!("  - {}: {}\n", k, v);
     }
-    md += &format!("- [Artifacts](/artifacts/?type={})\n", ty.name); // TODO: escape
+    let query = GetArtifactsQuery::default().filter_type(&ty.name);
+    let total = query
+        .count_artifacts(&mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    md += &format!("- [Artifacts]({}) ({})\n", query.to_url(), total);
+
+    let artifacts = query
+        .order_by(ArtifactOrderByField::UpdateTime, false)
+        .per_page(type_stats::SAMPLE_LIMIT)
+        .get_artifacts(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|x| Artifact::from((types[0].clone(), x)))
+        .collect::<Vec<_>>();
+
+    let mut stats = TypeStats::new(ty.properties.keys());
+    for x in &artifacts {
+        stats.add(&x.properties, &x.custom_properties);
+    }
+    md += "\n## Property Statistics\n\n";
+    md += &format!(
+        "Computed from the latest {} of {} artifacts.\n\n",
+        stats.samples(),
+        total
+    );
+    md += &stats.properties_md();
+
+    if !artifacts.is_empty() {
+        md += "\n## Recent Artifacts\n\n";
+        md += "| id | name | state | update-time |\n";
+        md += "|----|------|-------|-------------|\n";
+        for x in artifacts.iter().take(type_stats::RECENT_LIMIT) {
+            md += &format!(
+                "| [{}](/artifacts/{}) | {} | {} | {} |\n",
+                x.id,
+                x.id,
+                x.name.as_ref().map_or("", |x| x.as_str()),
+                x.state,
+                tz.convert(x.mtime)
+            );
+        }
+    }
+
+    let nodes = artifacts
+        .iter()
+        .take(type_stats::EVENT_SAMPLE_LIMIT)
+        .map(|x| NodeId::Artifact(x.id))
+        .collect::<Vec<_>>();
+    let counts = type_stats::count_neighbor_types(&mut store, &nodes)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !counts.is_empty() {
+        md += "\n## Producers and Consumers\n\n";
+        md += &format!(
+            "Counted from the events of the latest {} artifacts.\n\n",
+            nodes.len()
+        );
+        md +=
+            "| execution type | produced | consumed |\n|----------------|----------|----------|\n";
+        for (type_name, count) in counts {
+            md += &format!(
+                "| [{}]({}) | {} | {} |\n",
+                type_name,
+                GetExecutionsQuery::default()
+                    .filter_type(&type_name)
+                    .to_url(),
+                count.produced,
+                count.consumed
+            );
+        }
+    }
 
     Ok(response::markdown(&md))
 }
@@ -1,10 +1,17 @@
-use crate::hook::GeneralOutput;
+use crate::graph::{BatchStore, Node, NodeId};
+use crate::hook::{GeneralOutput, ItemType};
 use crate::mlmd::artifact::{Artifact, ArtifactOrderByField};
-use crate::time::DateTime;
-use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
-use std::collections::{HashMap, HashSet};
-use std::time::Duration;
+use crate::mlmd::event::{Event, EventType};
+use crate::mlmd::list::{self, Cursor};
+use crate::web::handlers::contexts;
+use crate::web::handlers::lineage::{self, graph_formats_md, GetGraphQuery};
+use crate::web::handlers::plot::{self, PlotTarget};
+use crate::web::pager;
+use crate::web::query::{self, ListQuery};
+use crate::web::{export, response, time_range, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use futures::Stream;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[get("/artifacts/{id}/contents/{name}")]
 async fn get_artifact_content(
@@ -55,79 +62,34 @@ async fn get_artifact_content(
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct GetArtifactsQuery {
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
-    #[serde(default)]
-    pub order_by: ArtifactOrderByField,
-    #[serde(default)]
-    pub asc: bool,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_start: Option<DateTime>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_end: Option<DateTime>,
-}
+pub type GetArtifactsQuery = ListQuery<ArtifactOrderByField>;
 
 impl GetArtifactsQuery {
-    // TODO
     pub async fn get_artifacts(
         &self,
         store: &mut mlmd::MetadataStore,
+        conn: &mut sqlx::AnyConnection,
     ) -> anyhow::Result<Vec<mlmd::metadata::Artifact>> {
-        let context_id = if let Some(context) = self.context {
-            Some(mlmd::metadata::ContextId::new(context))
-        } else {
-            None
-        };
-
-        let mut request = store.get_artifacts().limit(self.limit.unwrap_or(100));
-        if let Some(c) = context_id {
-            request = request.context(c)
-        }
-        if let Some(n) = self.offset {
-            request = request.offset(n);
-        }
-        if let Some(n) = &self.type_name {
-            if let Some(m) = &self.name {
-                request = request.type_and_name(n, m);
-            } else {
-                request = request.ty(n);
-            }
-        }
-        request = request.order_by(self.order_by.into(), self.asc);
-
-        match (self.mtime_start, self.mtime_end) {
-            (None, None) => {}
-            (Some(start), None) => {
-                request =
-                    request.update_time(Duration::from_millis(start.timestamp_millis() as u64)..);
-            }
-            (None, Some(end)) => {
-                request =
-                    request.update_time(..Duration::from_millis(end.timestamp_millis() as u64));
-            }
-            (Some(start), Some(end)) => {
-                request = request.update_time(
-                    Duration::from_millis(start.timestamp_millis() as u64)
-                        ..Duration::from_millis(end.timestamp_millis() as u64),
-                );
-            }
+        let ids = self.get_item_ids(conn, ItemType::Artifact).await?;
+        let mut artifacts = Vec::new();
+        for ids in ids.chunks(list::BATCH_SIZE) {
+            artifacts.extend(
+                store
+                    .get_artifacts()
+                    .ids(ids.iter().copied().map(mlmd::metadata::ArtifactId::new))
+                    .execute()
+                    .await?,
+            );
         }
+        query::sort_by_ids(&mut artifacts, &ids, |x| x.id.get());
+        Ok(artifacts)
+    }
 
-        Ok(request.execute().await?)
+    pub async fn count_artifacts(&self, conn: &mut sqlx::AnyConnection) -> anyhow::Result<usize> {
+        self.count_items(conn, ItemType::Artifact).await
     }
 
-    async fn get_artifact_types(
+    pub async fn get_artifact_types(
         &self,
         store: &mut mlmd::MetadataStore,
         artifacts: &[mlmd::metadata::Artifact],
@@ -143,88 +105,89 @@ impl GetArtifactsQuery {
             .collect())
     }
 
-    fn prev(&self) -> Self {
-        let mut this = self.clone();
-        this.offset = Some(
-            self.offset
-                .unwrap_or(0)
-                .saturating_sub(self.limit.unwrap_or(100)),
-        );
-        this
+    /// Gets all the artifacts matching this query page by page regardless of the pagination parameters.
+    pub fn get_all_artifacts(
+        &self,
+        store: mlmd::MetadataStore,
+        conn: sqlx::AnyConnection,
+    ) -> impl Stream<Item = anyhow::Result<Vec<Artifact>>> {
+        let query = Some(self.all().per_page(export::BATCH_SIZE));
+        futures::stream::try_unfold(
+            (query, store, conn),
+            |(query, mut store, mut conn)| async move {
+                let query = match query {
+                    Some(query) => query,
+                    None => return Ok(None),
+                };
+                let batch = query.get_artifacts(&mut store, &mut conn).await?;
+                let types = query.get_artifact_types(&mut store, &batch).await?;
+                let next = if batch.len() < query.limit() {
+                    None
+                } else {
+                    Some(query.next(&batch))
+                };
+                let artifacts = batch
+                    .into_iter()
+                    .map(|x| Artifact::from((types[&x.type_id].clone(), x)))
+                    .collect();
+                Ok(Some((artifacts, (next, store, conn))))
+            },
+        )
     }
 
-    fn next(&self) -> Self {
+    fn next(&self, artifacts: &[mlmd::metadata::Artifact]) -> Self {
         let mut this = self.clone();
         this.offset = Some(self.offset() + self.limit());
+        this.cursor = artifacts.last().map(|x| self.cursor(x));
+        this.before = None;
         this
     }
 
-    fn reset_mtime_start(&self) -> Self {
-        let mut this = self.clone();
-        this.mtime_start = None;
-        this
-    }
-
-    fn reset_mtime_end(&self) -> Self {
-        let mut this = self.clone();
-        this.mtime_end = None;
-        this
-    }
-
-    fn filter_type(&self, type_name: &str) -> Self {
-        let mut this = self.clone();
-        this.type_name = Some(type_name.to_owned());
-        this.offset = None;
-        this
-    }
-
-    fn order_by(&self, field: ArtifactOrderByField, asc: bool) -> Self {
+    fn prev(&self, artifacts: &[mlmd::metadata::Artifact]) -> Self {
         let mut this = self.clone();
-        this.order_by = field;
-        this.asc = asc;
-        this.offset = None;
+        let offset = self.offset().saturating_sub(self.limit());
+        this.offset = Some(offset);
+        this.cursor = None;
+        // The first page is cheap to get by the offset, which also avoids a cursor of an empty page.
+        this.before = if offset == 0 {
+            None
+        } else {
+            artifacts.first().map(|x| self.cursor(x))
+        };
         this
     }
 
-    pub fn to_url(&self) -> String {
-        format!("/artifacts/?{}", self.to_qs())
-    }
-
-    pub fn to_qs(&self) -> String {
-        let qs = serde_json::to_value(self)
-            .expect("unreachable")
-            .as_object()
-            .expect("unwrap")
-            .into_iter()
-            .map(|(k, v)| {
-                format!(
-                    "{}={}",
-                    k,
-                    v.to_string().trim_matches('"').replace('+', "%2B") // TODO: escape
-                )
-            })
-            .collect::<Vec<_>>();
-        qs.join("&")
-    }
-
-    fn offset(&self) -> usize {
-        self.offset.unwrap_or(0)
-    }
-
-    fn limit(&self) -> usize {
-        self.limit.unwrap_or(100)
+    fn cursor(&self, x: &mlmd::metadata::Artifact) -> Cursor {
+        let id = x.id.get();
+        match self.order_by {
+            ArtifactOrderByField::Id => Cursor::id(id),
+            ArtifactOrderByField::Name => Cursor::name(id, x.name.as_deref()),
+            ArtifactOrderByField::CreateTime => Cursor::time(id, x.create_time_since_epoch),
+            ArtifactOrderByField::UpdateTime => Cursor::time(id, x.last_update_time_since_epoch),
+        }
     }
 }
 
 #[get("/artifacts/")]
 pub async fn get_artifacts(
     config: web::Data<Config>,
-    query: web::Query<GetArtifactsQuery>,
+    mut query: web::Query<GetArtifactsQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    query
+        .resolve_context_filters(&mut conn, ItemType::Artifact)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let artifacts = query
-        .get_artifacts(&mut store)
+        .get_artifacts(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let total = query
+        .count_artifacts(&mut conn)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
     let artifact_types = query
@@ -234,61 +197,43 @@ pub async fn get_artifacts(
 
     let mut md = "# Artifacts\n".to_string();
 
-    let mut pager_md = String::new();
-    if query.offset() != 0 {
-        pager_md += &format!(" [<<]({})", query.prev().to_url());
-    } else {
-        pager_md += " <<";
-    }
-    pager_md += &format!(
-        " {}~{} ",
-        query.offset() + 1,
-        query.offset() + artifacts.len()
+    let pager_md = pager::pager_md(
+        query.offset(),
+        query.limit(),
+        artifacts.len(),
+        total,
+        (
+            &query.prev(&artifacts).to_url(),
+            &query.next(&artifacts).to_url(),
+        ),
+        |offset| query.page(offset).to_url(),
+        |limit| query.per_page(limit).to_url(),
     );
-    if artifacts.len() == query.limit() {
-        pager_md += &format!("[>>]({})", query.next().to_url());
-    } else {
-        pager_md += ">>";
-    }
     md += &pager_md;
 
-    md += &format!(
-        r#",
-Update Time: <input type="date" id="start_date" {} onchange="filter_start_date()"> ~
-             <input type="date" id="end_date" {} onchange="filter_end_date()">
-
-<script type="text/javascript">
-function filter_start_date() {{
-  var v = document.getElementById("start_date").value;
-  location.href = "{}&mtime-start=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-<script type="text/javascript">
-function filter_end_date() {{
-  var v = document.getElementById("end_date").value;
-  location.href = "{}&mtime-end=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-"#,
-        if let Some(v) = &query.mtime_start {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        if let Some(v) = &query.mtime_end {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        query.reset_mtime_start().to_url(),
-        query.reset_mtime_end().to_url()
+    md += ",\n";
+    md += &time_range::mtime_range_filter(
+        tz,
+        query.mtime_start,
+        query.mtime_end,
+        query.mtime_last,
+        |start, end| query.mtime_range(start, end).to_url(),
+        |period| query.mtime_last(period).to_url(),
     );
+    md += "\n";
+    md += &export::export_links_md(&query);
+    md += " | ";
+    md += &plot::plot_links_md(PlotTarget::Artifacts(query.0.clone()));
+    md += " | ";
+    md += &views::save_link_md(&req);
+    md += &contexts::context_filters_md(&query);
+    md += "\n";
 
     md += "\n";
     md += &format!(
         "| id{}{} | type | name{}{} | state | update-time{}{} | summary |\n",
         if query.order_by == ArtifactOrderByField::Id && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -296,7 +241,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ArtifactOrderByField::Id && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -304,7 +249,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ArtifactOrderByField::Name && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -312,7 +257,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ArtifactOrderByField::Name && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -320,7 +265,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ArtifactOrderByField::UpdateTime && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -330,7 +275,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ArtifactOrderByField::UpdateTime && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -352,14 +297,14 @@ function filter_end_date() {{
         .await?;
     for a in artifacts {
         md += &format!(
-            "| [{}]({}) | [{}]({}) | {} | {} | {} | {} |\n",
+            "| [{}](/artifacts/{}) | [{}]({}) | {} | {} | {} | {} |\n",
+            a.id,
             a.id,
-            format!("/artifacts/{}", a.id),
             a.type_name,
             query.filter_type(&a.type_name).to_url(),
             a.name.as_ref().map_or("", |x| x.as_str()),
             a.state,
-            a.mtime,
+            tz.convert(a.mtime),
             a.summary.as_ref().map_or("", |x| x.as_str())
         );
     }
@@ -382,7 +327,9 @@ pub async fn get_artifact(
     config: web::Data<Config>,
     path: web::Path<(String,)>,
     query: web::Query<GetArtifactQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let id_or_name = &path.0;
     let mut store = config.connect_metadata_store().await?;
 
@@ -403,9 +350,9 @@ pub async fn get_artifact(
                     .await
                     .map_err(actix_web::error::ErrorInternalServerError)?
             } else {
-                return Err(actix_web::error::ErrorBadRequest(format!(
-                    "`type` query parameter must be specified"
-                )));
+                return Err(actix_web::error::ErrorBadRequest(
+                    "`type` query parameter must be specified",
+                ));
             }
         }
     };
@@ -450,264 +397,499 @@ pub async fn get_artifact(
         md += &format!("- **URI**: {}\n", x);
     }
     md += &format!("- **State**: {}\n", artifact.state);
-    md += &format!("- **Create Time**: {}\n", artifact.ctime);
-    md += &format!("- **Update Time**: {}\n", artifact.mtime);
+    md += &format!("- **Create Time**: {}\n", tz.convert(artifact.ctime));
+    md += &format!("- **Update Time**: {}\n", tz.convert(artifact.mtime));
 
     if !artifact.properties.is_empty() {
-        md += &format!("- **Properties**:\n");
+        md += "- **Properties**:\n";
         for (k, v) in &artifact.properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
     if !artifact.custom_properties.is_empty() {
-        md += &format!("- **Custom Properties**:\n");
+        md += "- **Custom Properties**:\n";
         for (k, v) in &artifact.custom_properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
     if !artifact.extra_properties.is_empty() {
-        md += &format!("- **Extra Properties**:\n");
+        md += "- **Extra Properties**:\n";
         for (k, v) in &artifact.extra_properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
 
-    let contexts_len = store
+    let contexts = store
         .get_contexts()
         .artifact(mlmd::metadata::ArtifactId::new(artifact.id))
-        .count()
+        .execute()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
-    let events_len = store
+    let events = store
         .get_events()
         .artifact(mlmd::metadata::ArtifactId::new(artifact.id))
-        .count()
+        .execute()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
-    if contexts_len > 0 {
+    if !contexts.is_empty() {
+        let contexts_len = contexts.len();
+        let links = contexts::context_links_md(&mut store, contexts)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
         md += &format!(
-            "- [**Contexts**](/contexts/?artifact={}) ({})\n",
-            artifact.id, contexts_len
+            "- [**Contexts**](/contexts/?artifact={}) ({}): {}\n",
+            artifact.id, contexts_len, links
         );
     }
-    if events_len > 0 {
+    if !events.is_empty() {
         md += &format!(
             "- [**Events**](/events/?artifact={}) ({})\n",
-            artifact.id, events_len
+            artifact.id,
+            events.len()
         );
     }
 
-    md += &format!("- [**Graph**](/artifacts/{}/graph)\n", artifact.id);
+    md += &format!(
+        "- [**Graph**](/artifacts/{}/graph) ([explore](/lineage/explorer?node=A{}); {})\n",
+        artifact.id,
+        artifact.id,
+        graph_formats_md(&format!("/artifacts/{}/graph", artifact.id))
+    );
+    md += &format!(
+        "- [**Upstream**](/artifacts/{}/upstream) / [**Downstream**](/artifacts/{}/downstream)\n",
+        artifact.id, artifact.id
+    );
+
+    md += &event_executions_md(&config, &mut store, events).await?;
 
     Ok(response::markdown(&md))
 }
 
-#[get("/artifacts/{id}/graph")]
-pub async fn get_artifact_graph(
-    config: web::Data<Config>,
-    path: web::Path<(i32,)>,
-) -> actix_web::Result<HttpResponse> {
-    let id = path.0;
-    let mut store = config.connect_metadata_store().await?;
+/// Makes the markdown of the executions producing or consuming an artifact, grouped by event type.
+async fn event_executions_md(
+    config: &Config,
+    store: &mut mlmd::MetadataStore,
+    events: Vec<mlmd::metadata::Event>,
+) -> actix_web::Result<String> {
+    if events.is_empty() {
+        return Ok(String::new());
+    }
 
-    let graph = Graph::new(&mut store, id)
+    let ids = events
+        .iter()
+        .map(|e| e.execution_id.get())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let executions = BatchStore::new(store)
+        .get_executions(&ids)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
+    let executions = config
+        .hook_runner
+        .run_execution_summary_hook(executions)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
+
+    let mut events = events.into_iter().map(Event::from).collect::<Vec<_>>();
+    events.sort_by_key(|e| (e.path_string(), e.execution_id));
 
-    let svg = std::process::Command::new("dot")
-        .arg("-Tsvg")
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .ok()
-        .and_then(|mut child| {
-            use std::io::Write;
-
-            let writer = child.stdin.as_mut()?;
-            graph.render(writer).ok()?;
-            writer.flush().ok()?;
-            let output = child.wait_with_output().ok()?;
-            if !output.status.success() {
-                None
+    let mut md = String::new();
+    for &ty in EventType::ALL {
+        let events = events.iter().filter(|e| e.ty == ty).collect::<Vec<_>>();
+        if events.is_empty() {
+            continue;
+        }
+
+        md += &format!(
+            "\n## {} ({})\n\n",
+            if ty.is_output() {
+                "Producing Executions"
+            } else if ty.is_input() {
+                "Consuming Executions"
             } else {
-                Some(output.stdout)
-            }
-        });
+                "Executions"
+            },
+            ty
+        );
+        md += "| path | id | type | name | state | summary |\n";
+        md += "|------|----|------|------|-------|---------|\n";
+        for e in events {
+            let x = if let Some(x) = executions.get(&e.execution_id) {
+                x
+            } else {
+                continue;
+            };
+            md += &format!(
+                "| {} | [{}](/executions/{}) | {} | {} | {} | {} |\n",
+                e.path_string(),
+                x.id,
+                x.id,
+                x.type_name,
+                x.name.as_ref().map_or("", |x| x.as_str()),
+                x.state,
+                x.summary.as_ref().map_or("", |x| x.as_str())
+            );
+        }
+    }
+    Ok(md)
+}
+
+/// Maximum number of items in an upstream or downstream closure.
+const CLOSURE_LIMIT: usize = 10000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Upstream,
+    Downstream,
+}
 
-    if let Some(svg) = svg {
-        Ok(response::svg(&String::from_utf8(svg).expect("TODO")))
-    } else {
-        let mut buf = Vec::new();
-        graph.render(&mut buf).expect("TODO");
-        Ok(response::markdown(&String::from_utf8(buf).expect("TODO")))
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Upstream => write!(f, "upstream"),
+            Self::Downstream => write!(f, "downstream"),
+        }
     }
 }
 
-use crate::web::handlers::executions::{Edge, Node, NodeId};
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClosureFormat {
+    #[default]
+    Html,
+    Json,
+}
 
-#[derive(Debug)]
-struct Graph {
-    nodes: Vec<Node>,
-    edges: Vec<Edge>,
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetArtifactClosureQuery {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    pub format: ClosureFormat,
 }
 
-impl Graph {
-    async fn new(store: &mut mlmd::MetadataStore, artifact_id: i32) -> anyhow::Result<Self> {
-        let mut nodes = HashMap::new();
-        let mut edges = Vec::new();
-        let mut stack = vec![NodeId::Artifact(artifact_id)];
-        while let Some(curr) = stack.pop() {
-            if nodes.contains_key(&curr) {
-                continue;
-            }
-            let mut curr = match curr {
-                NodeId::Execution(id) => Node::Execution {
-                    node: fetch_execution(store, id).await?,
-                    inputs: 0,
-                    outputs: 0,
-                },
-                NodeId::Artifact(id) => Node::Artifact {
-                    node: fetch_artifact(store, id).await?,
-                    inputs: 0,
-                    outputs: 0,
-                },
-            };
+impl GetArtifactClosureQuery {
+    fn filter_type(&self, type_name: Option<&str>) -> Self {
+        let mut this = self.clone();
+        this.type_name = type_name.map(|x| x.to_owned());
+        this
+    }
 
-            let events = match &curr {
-                Node::Execution { node, .. } => {
-                    store
-                        .get_events()
-                        .execution(mlmd::metadata::ExecutionId::new(node.id))
-                        .execute()
-                        .await?
-                }
-                Node::Artifact { node, .. } => {
-                    store
-                        .get_events()
-                        .artifact(mlmd::metadata::ArtifactId::new(node.id))
-                        .execute()
-                        .await?
-                }
-            };
-            curr.set_in_out(&events);
-            nodes.insert(curr.id(), curr.clone());
-            anyhow::ensure!(
-                nodes.len() < 100,
-                "Too many executions and artifact to visualize"
-            );
+    fn max_depth(&self, max_depth: Option<usize>) -> Self {
+        let mut this = self.clone();
+        this.max_depth = max_depth;
+        this
+    }
 
-            for event in events {
-                if matches!(curr, Node::Artifact { .. }) {
-                    use mlmd::metadata::EventType::*;
-                    if event.artifact_id.get() == artifact_id
-                        || matches!(event.ty, Output | DeclaredOutput | InternalOutput)
-                    {
-                        let id = NodeId::Execution(event.execution_id.get());
-                        stack.push(id);
-                        if matches!(event.ty, Output | DeclaredOutput | InternalOutput) {
-                            edges.push(Edge {
-                                source: id,
-                                target: curr.id(),
-                                event: event.into(),
-                            });
-                        }
-                    }
-                } else {
-                    use mlmd::metadata::EventType::*;
-                    if matches!(event.ty, Input | DeclaredInput | InternalInput) {
-                        let id = NodeId::Artifact(event.artifact_id.get());
-                        stack.push(id);
-                        edges.push(Edge {
-                            source: id,
-                            target: curr.id(),
-                            event: event.into(),
-                        });
-                    }
-                }
-            }
-        }
+    fn format(&self, format: ClosureFormat) -> Self {
+        let mut this = self.clone();
+        this.format = format;
+        this
+    }
 
-        Ok(Self {
-            nodes: nodes.into_iter().map(|x| x.1).collect(),
-            edges,
-        })
+    fn to_url(&self, id: i32, direction: Direction) -> String {
+        format!(
+            "/artifacts/{}/{}?{}",
+            id,
+            direction,
+            crate::web::query::to_qs(self)
+        )
     }
+}
 
-    fn render<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        writeln!(writer, "digraph execution_graph {{")?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClosureItemKind {
+    Artifact,
+    Execution,
+}
 
-        for node in &self.nodes {
-            writeln!(writer, "{}[{}]", node.id(), node.attrs().join(","))?;
-        }
+/// Execution or artifact reached from an artifact by following events.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClosureItem {
+    pub depth: usize,
+    pub kind: ClosureItemKind,
+    pub id: i32,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 
-        for edge in &self.edges {
-            writeln!(
-                writer,
-                "{} -> {} [label={:?}];",
-                edge.source,
-                edge.target,
-                format!("{:?}:{:?}", edge.event.ty, edge.event.path)
-            )?;
+    /// Events leading from the origin artifact to this item.
+    pub events: Vec<Event>,
+}
+
+impl ClosureItem {
+    fn link_md(&self) -> String {
+        match self.kind {
+            ClosureItemKind::Artifact => format!("[A{}](/artifacts/{})", self.id, self.id),
+            ClosureItemKind::Execution => format!("[E{}](/executions/{})", self.id, self.id),
         }
+    }
 
-        writeln!(writer, "}}")?;
-        Ok(())
+    fn path_md(&self, origin: i32) -> String {
+        let mut md = format!("[A{}](/artifacts/{})", origin, origin);
+        for (i, e) in self.events.iter().enumerate() {
+            let step = e.path_string();
+            let arrow = if step.is_empty() {
+                "→".to_owned()
+            } else {
+                format!("→({})", step)
+            };
+            if i % 2 == 0 {
+                md += &format!(
+                    " {} [E{}](/executions/{})",
+                    arrow, e.execution_id, e.execution_id
+                );
+            } else {
+                md += &format!(
+                    " {} [A{}](/artifacts/{})",
+                    arrow, e.artifact_id, e.artifact_id
+                );
+            }
+        }
+        md
     }
 }
 
-async fn fetch_execution(
+/// Collects the items reachable from the artifact `id` in `direction`.
+///
+/// The items are expanded breadth-first, fetching each level in batches.
+async fn get_artifact_closure(
     store: &mut mlmd::MetadataStore,
     id: i32,
-) -> anyhow::Result<crate::mlmd::execution::Execution> {
-    let executions = store
-        .get_executions()
-        .id(mlmd::metadata::ExecutionId::new(id))
-        .execute()
-        .await?;
-    anyhow::ensure!(!executions.is_empty(), "no such execution: {}", id);
+    direction: Direction,
+    max_depth: Option<usize>,
+) -> anyhow::Result<Vec<ClosureItem>> {
+    let mut store = BatchStore::new(store);
+    let mut visited = HashSet::new();
+    let mut items = Vec::new();
+    visited.insert(NodeId::Artifact(id));
+    let mut frontier = vec![(NodeId::Artifact(id), Vec::<Event>::new())];
+    for depth in 1.. {
+        if frontier.is_empty() {
+            break;
+        }
+        if let Some(max_depth) = max_depth {
+            if depth > max_depth {
+                break;
+            }
+        }
 
-    let types = store
-        .get_execution_types()
-        .id(executions[0].type_id)
-        .execute()
-        .await?;
-    anyhow::ensure!(
-        !executions.is_empty(),
-        "no such execution tyep: {}",
-        executions[0].type_id.get()
-    );
+        let chains = frontier.into_iter().collect::<HashMap<_, _>>();
+        let mut next = Vec::new();
+        let ids = chains.keys().copied().collect::<Vec<_>>();
+        for (curr, event) in store.get_events(&ids).await? {
+            let event = Event::from(event);
+
+            // Downstream goes artifact -> consuming execution -> output artifact,
+            // and upstream goes artifact -> producing execution -> input artifact.
+            let (next_id, follow) = match curr {
+                NodeId::Artifact(_) => (
+                    NodeId::Execution(event.execution_id),
+                    match direction {
+                        Direction::Downstream => event.ty.is_input(),
+                        Direction::Upstream => event.ty.is_output(),
+                    },
+                ),
+                NodeId::Execution(_) => (
+                    NodeId::Artifact(event.artifact_id),
+                    match direction {
+                        Direction::Downstream => event.ty.is_output(),
+                        Direction::Upstream => event.ty.is_input(),
+                    },
+                ),
+            };
+            if !follow || !visited.insert(next_id) {
+                continue;
+            }
+            anyhow::ensure!(
+                visited.len() <= CLOSURE_LIMIT,
+                "too many {} items (> {})",
+                direction,
+                CLOSURE_LIMIT
+            );
+
+            let mut chain = chains[&curr].clone();
+            chain.push(event);
+            next.push((next_id, chain));
+        }
 
-    Ok(crate::mlmd::execution::Execution::from((
-        types[0].clone(),
-        executions[0].clone(),
-    )))
+        let ids = next.iter().map(|x| x.0).collect::<Vec<_>>();
+        let nodes = store
+            .get_nodes(&ids)
+            .await?
+            .into_iter()
+            .map(|node| (node.id(), node))
+            .collect::<HashMap<_, _>>();
+        for (next_id, chain) in &next {
+            let node = if let Some(node) = nodes.get(next_id) {
+                node
+            } else {
+                continue;
+            };
+            let (kind, id, name) = match node {
+                Node::Artifact { node, .. } => {
+                    (ClosureItemKind::Artifact, node.id, node.name.clone())
+                }
+                Node::Execution { node, .. } => {
+                    (ClosureItemKind::Execution, node.id, node.name.clone())
+                }
+            };
+            items.push(ClosureItem {
+                depth,
+                kind,
+                id,
+                type_name: node.type_name().to_owned(),
+                name,
+                events: chain.clone(),
+            });
+        }
+        frontier = next;
+    }
+    Ok(items)
 }
 
-async fn fetch_artifact(
-    store: &mut mlmd::MetadataStore,
+async fn artifact_closure(
+    config: web::Data<Config>,
     id: i32,
-) -> anyhow::Result<crate::mlmd::artifact::Artifact> {
+    direction: Direction,
+    query: GetArtifactClosureQuery,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
     let artifacts = store
         .get_artifacts()
         .id(mlmd::metadata::ArtifactId::new(id))
         .execute()
-        .await?;
-    anyhow::ensure!(!artifacts.is_empty(), "no such artifact: {}", id);
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if artifacts.is_empty() {
+        return Err(actix_web::error::ErrorNotFound(format!(
+            "no such artifact: {}",
+            id
+        )));
+    }
 
-    let types = store
-        .get_artifact_types()
-        .id(artifacts[0].type_id)
-        .execute()
-        .await?;
-    anyhow::ensure!(
-        !artifacts.is_empty(),
-        "no such artifact tyep: {}",
-        artifacts[0].type_id.get()
+    let items = get_artifact_closure(&mut store, id, direction, query.max_depth)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let type_names = items
+        .iter()
+        .map(|x| x.type_name.clone())
+        .collect::<std::collections::BTreeSet<_>>();
+    let items = items
+        .into_iter()
+        .filter(|x| match &query.type_name {
+            Some(t) => *t == x.type_name,
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    if query.format == ClosureFormat::Json {
+        let json =
+            serde_json::to_string(&items).map_err(actix_web::error::ErrorInternalServerError)?;
+        return Ok(response::json(&json));
+    }
+
+    let mut md = format!(
+        "# {} of [Artifact {}](/artifacts/{})\n\n",
+        if direction == Direction::Upstream {
+            "Upstream"
+        } else {
+            "Downstream"
+        },
+        id,
+        id
+    );
+    md += &format!(
+        "- **Type**: {}\n",
+        std::iter::once(None)
+            .chain(type_names.iter().map(Some))
+            .map(|t| {
+                let label = t.map_or("all", |t| t.as_str());
+                if t == query.type_name.as_ref() {
+                    format!("**{}**", label)
+                } else {
+                    format!(
+                        "[{}]({})",
+                        label,
+                        query
+                            .filter_type(t.map(|t| t.as_str()))
+                            .to_url(id, direction)
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    md += &format!(
+        "- **Max depth**: {}\n",
+        [None, Some(1), Some(2), Some(4), Some(8)]
+            .iter()
+            .map(|&d| {
+                let label = d.map_or("unlimited".to_owned(), |d| d.to_string());
+                if d == query.max_depth {
+                    format!("**{}**", label)
+                } else {
+                    format!("[{}]({})", label, query.max_depth(d).to_url(id, direction))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
     );
+    md += &format!(
+        "- **Items**: {} ([JSON]({}))\n",
+        items.len(),
+        query.format(ClosureFormat::Json).to_url(id, direction)
+    );
+
+    md += "\n| depth | id | type | name | path |\n";
+    md += "|-------|----|------|------|------|\n";
+    for x in &items {
+        md += &format!(
+            "| {} | {} | {} | {} | {} |\n",
+            x.depth,
+            x.link_md(),
+            x.type_name,
+            x.name.as_ref().map_or("", |x| x.as_str()),
+            x.path_md(id)
+        );
+    }
+    Ok(response::markdown(&md))
+}
 
-    Ok(crate::mlmd::artifact::Artifact::from((
-        types[0].clone(),
-        artifacts[0].clone(),
-    )))
+#[get("/artifacts/{id}/upstream")]
+pub async fn get_artifact_upstream(
+    config: web::Data<Config>,
+    path: web::Path<(i32,)>,
+    query: web::Query<GetArtifactClosureQuery>,
+) -> actix_web::Result<HttpResponse> {
+    artifact_closure(config, path.0, Direction::Upstream, query.into_inner()).await
+}
+
+#[get("/artifacts/{id}/downstream")]
+pub async fn get_artifact_downstream(
+    config: web::Data<Config>,
+    path: web::Path<(i32,)>,
+    query: web::Query<GetArtifactClosureQuery>,
+) -> actix_web::Result<HttpResponse> {
+    artifact_closure(config, path.0, Direction::Downstream, query.into_inner()).await
+}
+
+#[get("/artifacts/{id}/graph")]
+pub async fn get_artifact_graph(
+    config: web::Data<Config>,
+    path: web::Path<(i32,)>,
+    query: web::Query<GetGraphQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let graph = lineage::get_graph(&config, NodeId::Artifact(path.0)).await?;
+    lineage::graph_response(&graph, config.graph_style(), query.format)
 }
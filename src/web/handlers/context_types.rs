@@ -1,4 +1,7 @@
 use crate::mlmd::context::{ContextTypeDetail, ContextTypeSummary};
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::contexts::GetContextsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
 use crate::web::link::Link;
 use crate::web::{response, Config};
 use actix_web::{get, web, HttpResponse};
@@ -59,12 +62,27 @@ async fn get_context_type_detail(
 
     md += &format!("- ID: {}\n", ty.id);
     md += &format!("- Name: {}\n", ty.name);
-    md += &format!("- Properties:\n");
+    md += "- Properties:\n";
 
     for (k, v) in &ty.properties {
         md += &format!("  - {}: {}\n", k, v);
     }
-    md += &format!("- [Contexts](/contexts/?type={})\n", ty.name); // TODO: escape
+    md += &format!(
+        "- [Contexts]({})\n",
+        GetContextsQuery::default().filter_type(&ty.name).to_url()
+    );
+    md += &format!(
+        "- [Executions]({})\n",
+        GetExecutionsQuery::default()
+            .filter_context_type(Some(&ty.name))
+            .to_url()
+    );
+    md += &format!(
+        "- [Artifacts]({})\n",
+        GetArtifactsQuery::default()
+            .filter_context_type(Some(&ty.name))
+            .to_url()
+    );
 
     Ok(response::markdown(&md))
 }
@@ -1,10 +1,15 @@
-use crate::hook::GeneralOutput;
+use crate::hook::{GeneralOutput, ItemType};
 use crate::mlmd::context::{Context, ContextOrderByField};
-use crate::time::DateTime;
-use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use crate::mlmd::hierarchy::{self, ContextHierarchy};
+use crate::mlmd::list::{self, Cursor};
+use crate::web::handlers::diff;
+use crate::web::handlers::plot::{self, PlotTarget};
+use crate::web::pager;
+use crate::web::query::{self, ListQuery, OrderByField};
+use crate::web::{export, response, time_range, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use futures::Stream;
 use std::collections::{HashMap, HashSet};
-use std::time::Duration;
 
 #[get("/contexts/{id}/contents/{name}")]
 async fn get_context_content(
@@ -55,75 +60,31 @@ async fn get_context_content(
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct GetContextsQuery {
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub execution: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub artifact: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
-    #[serde(default)]
-    pub order_by: ContextOrderByField,
-    #[serde(default)]
-    pub asc: bool,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_start: Option<DateTime>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_end: Option<DateTime>,
-}
+pub type GetContextsQuery = ListQuery<ContextOrderByField>;
 
 impl GetContextsQuery {
-    // TODO
-    async fn get_contexts(
+    pub async fn get_contexts(
         &self,
         store: &mut mlmd::MetadataStore,
+        conn: &mut sqlx::AnyConnection,
     ) -> anyhow::Result<Vec<mlmd::metadata::Context>> {
-        let mut request = store.get_contexts().limit(self.limit.unwrap_or(100));
-        if let Some(c) = self.execution {
-            request = request.execution(mlmd::metadata::ExecutionId::new(c));
-        }
-        if let Some(c) = self.artifact {
-            request = request.artifact(mlmd::metadata::ArtifactId::new(c));
-        }
-        if let Some(n) = self.offset {
-            request = request.offset(n);
-        }
-        if let Some(n) = &self.type_name {
-            if let Some(m) = &self.name {
-                request = request.type_and_name(n, m);
-            } else {
-                request = request.ty(n);
-            }
-        }
-        request = request.order_by(self.order_by.into(), self.asc);
-
-        match (self.mtime_start, self.mtime_end) {
-            (None, None) => {}
-            (Some(start), None) => {
-                request =
-                    request.update_time(Duration::from_millis(start.timestamp_millis() as u64)..);
-            }
-            (None, Some(end)) => {
-                request =
-                    request.update_time(..Duration::from_millis(end.timestamp_millis() as u64));
-            }
-            (Some(start), Some(end)) => {
-                request = request.update_time(
-                    Duration::from_millis(start.timestamp_millis() as u64)
-                        ..Duration::from_millis(end.timestamp_millis() as u64),
-                );
-            }
+        let ids = self.get_item_ids(conn, ItemType::Context).await?;
+        let mut contexts = Vec::new();
+        for ids in ids.chunks(list::BATCH_SIZE) {
+            contexts.extend(
+                store
+                    .get_contexts()
+                    .ids(ids.iter().copied().map(mlmd::metadata::ContextId::new))
+                    .execute()
+                    .await?,
+            );
         }
+        query::sort_by_ids(&mut contexts, &ids, |x| x.id.get());
+        Ok(contexts)
+    }
 
-        Ok(request.execute().await?)
+    pub async fn count_contexts(&self, conn: &mut sqlx::AnyConnection) -> anyhow::Result<usize> {
+        self.count_items(conn, ItemType::Context).await
     }
 
     async fn get_context_types(
@@ -142,74 +103,66 @@ impl GetContextsQuery {
             .collect())
     }
 
-    fn prev(&self) -> Self {
-        let mut this = self.clone();
-        this.offset = Some(
-            self.offset
-                .unwrap_or(0)
-                .saturating_sub(self.limit.unwrap_or(100)),
-        );
-        this
+    /// Gets all the contexts matching this query page by page regardless of the pagination parameters.
+    pub fn get_all_contexts(
+        &self,
+        store: mlmd::MetadataStore,
+        conn: sqlx::AnyConnection,
+    ) -> impl Stream<Item = anyhow::Result<Vec<Context>>> {
+        let query = Some(self.all().per_page(export::BATCH_SIZE));
+        futures::stream::try_unfold(
+            (query, store, conn),
+            |(query, mut store, mut conn)| async move {
+                let query = match query {
+                    Some(query) => query,
+                    None => return Ok(None),
+                };
+                let batch = query.get_contexts(&mut store, &mut conn).await?;
+                let types = query.get_context_types(&mut store, &batch).await?;
+                let next = if batch.len() < query.limit() {
+                    None
+                } else {
+                    Some(query.next(&batch))
+                };
+                let contexts = batch
+                    .into_iter()
+                    .map(|x| Context::from((types[&x.type_id].clone(), x)))
+                    .collect();
+                Ok(Some((contexts, (next, store, conn))))
+            },
+        )
     }
 
-    fn next(&self) -> Self {
+    fn next(&self, contexts: &[mlmd::metadata::Context]) -> Self {
         let mut this = self.clone();
         this.offset = Some(self.offset() + self.limit());
+        this.cursor = contexts.last().map(|x| self.cursor(x));
+        this.before = None;
         this
     }
 
-    fn reset_mtime_start(&self) -> Self {
-        let mut this = self.clone();
-        this.mtime_start = None;
-        this.offset = None;
-        this
-    }
-
-    fn reset_mtime_end(&self) -> Self {
+    fn prev(&self, contexts: &[mlmd::metadata::Context]) -> Self {
         let mut this = self.clone();
-        this.mtime_end = None;
-        this.offset = None;
-        this
-    }
-
-    fn filter_type(&self, type_name: &str) -> Self {
-        let mut this = self.clone();
-        this.type_name = Some(type_name.to_owned());
-        this.offset = None;
-        this
-    }
-
-    fn order_by(&self, field: ContextOrderByField, asc: bool) -> Self {
-        let mut this = self.clone();
-        this.order_by = field;
-        this.asc = asc;
-        this.offset = None;
+        let offset = self.offset().saturating_sub(self.limit());
+        this.offset = Some(offset);
+        this.cursor = None;
+        // The first page is cheap to get by the offset, which also avoids a cursor of an empty page.
+        this.before = if offset == 0 {
+            None
+        } else {
+            contexts.first().map(|x| self.cursor(x))
+        };
         this
     }
 
-    fn to_url(&self) -> String {
-        let qs = serde_json::to_value(self)
-            .expect("unreachable")
-            .as_object()
-            .expect("unwrap")
-            .into_iter()
-            .map(|(k, v)| {
-                format!(
-                    "{}={}",
-                    k,
-                    v.to_string().trim_matches('"').replace('+', "%2B") // TODO: escape
-                )
-            })
-            .collect::<Vec<_>>();
-        format!("/contexts/?{}", qs.join("&"))
-    }
-
-    fn offset(&self) -> usize {
-        self.offset.unwrap_or(0)
-    }
-
-    fn limit(&self) -> usize {
-        self.limit.unwrap_or(100)
+    fn cursor(&self, x: &mlmd::metadata::Context) -> Cursor {
+        let id = x.id.get();
+        match self.order_by {
+            ContextOrderByField::Id => Cursor::id(id),
+            ContextOrderByField::Name => Cursor::name(id, Some(&x.name)),
+            ContextOrderByField::CreateTime => Cursor::time(id, x.create_time_since_epoch),
+            ContextOrderByField::UpdateTime => Cursor::time(id, x.last_update_time_since_epoch),
+        }
     }
 }
 
@@ -217,11 +170,18 @@ impl GetContextsQuery {
 pub async fn get_contexts(
     config: web::Data<Config>,
     query: web::Query<GetContextsQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
 
     let contexts = query
-        .get_contexts(&mut store)
+        .get_contexts(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let total = query
+        .count_contexts(&mut conn)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
     let context_types = query
@@ -230,61 +190,42 @@ pub async fn get_contexts(
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut md = "# Contexts\n".to_string();
-    let mut pager_md = String::new();
-    if query.offset() != 0 {
-        pager_md += &format!(" [<<]({})", query.prev().to_url());
-    } else {
-        pager_md += " <<";
-    }
-    pager_md += &format!(
-        " {}~{} ",
-        query.offset() + 1,
-        query.offset() + contexts.len()
+    let pager_md = pager::pager_md(
+        query.offset(),
+        query.limit(),
+        contexts.len(),
+        total,
+        (
+            &query.prev(&contexts).to_url(),
+            &query.next(&contexts).to_url(),
+        ),
+        |offset| query.page(offset).to_url(),
+        |limit| query.per_page(limit).to_url(),
     );
-    if contexts.len() == query.limit() {
-        pager_md += &format!("[>>]({})", query.next().to_url());
-    } else {
-        pager_md += ">>";
-    }
 
     md += &pager_md;
-    md += &format!(
-        r#",
-Update Time: <input type="date" id="start_date" {} onchange="filter_start_date()"> ~
-             <input type="date" id="end_date" {} onchange="filter_end_date()">
-
-<script type="text/javascript">
-function filter_start_date() {{
-  var v = document.getElementById("start_date").value;
-  location.href = "{}&mtime-start=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-<script type="text/javascript">
-function filter_end_date() {{
-  var v = document.getElementById("end_date").value;
-  location.href = "{}&mtime-end=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-"#,
-        if let Some(v) = &query.mtime_start {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        if let Some(v) = &query.mtime_end {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        query.reset_mtime_start().to_url(),
-        query.reset_mtime_end().to_url()
+    md += ",\n";
+    md += &time_range::mtime_range_filter(
+        tz,
+        query.mtime_start,
+        query.mtime_end,
+        query.mtime_last,
+        |start, end| query.mtime_range(start, end).to_url(),
+        |period| query.mtime_last(period).to_url(),
     );
+    md += "\n";
+    md += &export::export_links_md(&query);
+    md += " | ";
+    md += &plot::plot_links_md(PlotTarget::Contexts(query.0.clone()));
+    md += " | ";
+    md += &views::save_link_md(&req);
+    md += " | [Tree](/contexts/tree)\n";
 
     md += "\n";
     md += &format!(
         "| id{}{} | type | name{}{} | update-time{}{} | summary |\n",
         if query.order_by == ContextOrderByField::Id && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -292,7 +233,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ContextOrderByField::Id && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -300,7 +241,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ContextOrderByField::Name && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -308,7 +249,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ContextOrderByField::Name && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -316,7 +257,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ContextOrderByField::UpdateTime && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -326,7 +267,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ContextOrderByField::UpdateTime && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -348,13 +289,13 @@ function filter_end_date() {{
         .await?;
     for a in contexts {
         md += &format!(
-            "| [{}]({}) | [{}]({}) | {} | {} | {} |\n",
+            "| [{}](/contexts/{}) | [{}]({}) | {} | {} | {} |\n",
+            a.id,
             a.id,
-            format!("/contexts/{}", a.id),
             a.type_name,
             query.filter_type(&a.type_name).to_url(),
             a.name,
-            a.mtime,
+            tz.convert(a.mtime),
             a.summary.as_ref().map_or("", |x| x.as_str())
         );
     }
@@ -376,7 +317,9 @@ pub async fn get_context(
     config: web::Data<Config>,
     path: web::Path<(String,)>,
     query: web::Query<GetContextQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let id_or_name = &path.0;
     let mut store = config.connect_metadata_store().await?;
 
@@ -397,9 +340,9 @@ pub async fn get_context(
                     .await
                     .map_err(actix_web::error::ErrorInternalServerError)?
             } else {
-                return Err(actix_web::error::ErrorBadRequest(format!(
-                    "`type` query parameter must be specified"
-                )));
+                return Err(actix_web::error::ErrorBadRequest(
+                    "`type` query parameter must be specified",
+                ));
             }
         }
     };
@@ -435,22 +378,46 @@ pub async fn get_context(
         types[0].id.get()
     );
     md += &format!("- **Name**: {}\n", context.name);
-    md += &format!("- **Create Time**: {}\n", context.ctime);
-    md += &format!("- **Update Time**: {}\n", context.mtime);
+    md += &format!("- **Create Time**: {}\n", tz.convert(context.ctime));
+    md += &format!("- **Update Time**: {}\n", tz.convert(context.mtime));
 
     if !context.properties.is_empty() {
-        md += &format!("- **Properties**:\n");
+        md += "- **Properties**:\n";
         for (k, v) in &context.properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
     if !context.custom_properties.is_empty() {
-        md += &format!("- **Custom Properties**:\n");
+        md += "- **Custom Properties**:\n";
         for (k, v) in &context.custom_properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
 
+    let mut conn = config.connect_database().await?;
+    let hierarchy = ContextHierarchy::load_neighbors(&mut conn, &[context.id])
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let parent_ids = hierarchy.parents(context.id).collect::<Vec<_>>();
+    let child_ids = hierarchy.children(context.id).collect::<Vec<_>>();
+    let relatives = get_contexts_by_ids(&mut store, &[&parent_ids[..], &child_ids[..]].concat())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !parent_ids.is_empty() {
+        md += &format!(
+            "- **Parent Contexts**: {}\n",
+            context_ids_links_md(&relatives, &parent_ids)
+        );
+    }
+    if !child_ids.is_empty() {
+        md += &format!(
+            "- [**Child Contexts**](/contexts/tree?root={}) ({}): {}\n",
+            context.id,
+            child_ids.len(),
+            context_ids_links_md(&relatives, &child_ids)
+        );
+    }
+
     let associations_len = store
         .get_executions()
         .context(mlmd::metadata::ContextId::new(context.id))
@@ -476,6 +443,219 @@ pub async fn get_context(
             context.id, attributions_len
         );
     }
+    if !child_ids.is_empty() {
+        md += &format!(
+            "- **Including Descendants**: [Executions](/executions/?context={0}&descendants=true), \
+             [Artifacts](/artifacts/?context={0}&descendants=true)\n",
+            context.id
+        );
+    }
+    if associations_len > 0 {
+        md += &format!("- [**Timeline**](/timeline/?context={})\n", context.id);
+    }
+    md += &diff::diff_form_md("/diff/contexts", context.id);
 
     Ok(response::markdown(&md))
 }
+
+/// Maximum number of contexts shown in `/contexts/tree`.
+const CONTEXT_TREE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetContextTreeQuery {
+    /// Context to show the subtree of (all the trees if omitted).
+    pub root: Option<i32>,
+}
+
+#[get("/contexts/tree")]
+pub async fn get_context_tree(
+    config: web::Data<Config>,
+    query: web::Query<GetContextTreeQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+
+    let mut md = "# Context Tree\n".to_string();
+    let roots = if let Some(root) = query.root {
+        vec![root]
+    } else {
+        hierarchy::get_root_context_ids(&mut conn)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+    };
+    if roots.is_empty() {
+        md += "No nested contexts.\n";
+        return Ok(response::markdown(&md));
+    }
+    let hierarchy = ContextHierarchy::load_descendants(&mut conn, &roots, CONTEXT_TREE_LIMIT)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    // Collects the contexts breadth-first so that the upper levels are shown when truncated.
+    let mut ids = Vec::new();
+    let mut visited = HashSet::new();
+    let mut frontier = roots.clone();
+    while !frontier.is_empty() && ids.len() < CONTEXT_TREE_LIMIT {
+        let mut next = Vec::new();
+        for id in frontier {
+            if ids.len() < CONTEXT_TREE_LIMIT && visited.insert(id) {
+                ids.push(id);
+                next.extend(hierarchy.children(id));
+            }
+        }
+        frontier = next;
+    }
+    let parent_ids = if let Some(root) = query.root {
+        ContextHierarchy::load_neighbors(&mut conn, &[root])
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .parents(root)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let contexts = get_contexts_by_ids(&mut store, &[&ids[..], &parent_ids[..]].concat())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if !parent_ids.is_empty() {
+        let links = parent_ids
+            .iter()
+            .map(|id| match contexts.get(id) {
+                Some(c) => format!(
+                    "[{}: {}](/contexts/tree?root={})",
+                    c.type_name, c.name, c.id
+                ),
+                None => format!("[{}](/contexts/tree?root={})", id, id),
+            })
+            .collect::<Vec<_>>();
+        md += &format!("Parents: {} | [All](/contexts/tree)\n\n", links.join(", "));
+    }
+    if ids.len() == CONTEXT_TREE_LIMIT {
+        md += &format!(
+            "Only the first {} contexts are shown.\n\n",
+            CONTEXT_TREE_LIMIT
+        );
+    }
+
+    let shown = ids.into_iter().collect::<HashSet<_>>();
+    let mut stack = roots
+        .into_iter()
+        .rev()
+        .map(|id| (id, 0))
+        .collect::<Vec<_>>();
+    let mut rendered = HashSet::new();
+    while let Some((id, depth)) = stack.pop() {
+        if !shown.contains(&id) || !rendered.insert(id) {
+            continue;
+        }
+        let label = match contexts.get(&id) {
+            Some(c) => format!("{}: {}", c.type_name, c.name),
+            None => id.to_string(),
+        };
+        md += &format!(
+            "{}- [{}](/contexts/{}) ([executions](/executions/?context={3}&descendants=true), \
+             [artifacts](/artifacts/?context={3}&descendants=true))\n",
+            "  ".repeat(depth),
+            label,
+            id,
+            id
+        );
+        let children = hierarchy.children(id).collect::<Vec<_>>();
+        stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+    }
+
+    Ok(response::markdown(&md))
+}
+
+/// Makes the links to change the context filters of `query` (each prefixed by " | ").
+pub fn context_filters_md<F: OrderByField>(query: &ListQuery<F>) -> String {
+    let mut md = String::new();
+    if query.context.is_some() {
+        if query.descendants {
+            md += &format!(
+                " | [Exclude Descendant Contexts]({})",
+                query.filter_descendants(false).to_url()
+            );
+        } else {
+            md += &format!(
+                " | [Include Descendant Contexts]({})",
+                query.filter_descendants(true).to_url()
+            );
+        }
+    }
+    if let Some(context_type) = &query.context_type {
+        md += &format!(
+            " | Context Type: {} ([clear]({}))",
+            context_type,
+            query.filter_context_type(None).to_url()
+        );
+    }
+    md
+}
+
+/// Makes the links to `contexts` (e.g., the contexts of an artifact), labeled `${TYPE}: ${NAME}`.
+pub async fn context_links_md(
+    store: &mut mlmd::MetadataStore,
+    contexts: Vec<mlmd::metadata::Context>,
+) -> anyhow::Result<String> {
+    let types = GetContextsQuery::default()
+        .get_context_types(store, &contexts)
+        .await?;
+    let contexts = contexts
+        .into_iter()
+        .filter_map(|x| {
+            let ty = types.get(&x.type_id)?.clone();
+            Some((x.id.get(), Context::from((ty, x))))
+        })
+        .collect::<Vec<_>>();
+    let ids = contexts.iter().map(|x| x.0).collect::<Vec<_>>();
+    Ok(context_ids_links_md(&contexts.into_iter().collect(), &ids))
+}
+
+fn context_ids_links_md(contexts: &HashMap<i32, Context>, ids: &[i32]) -> String {
+    ids.iter()
+        .map(|id| match contexts.get(id) {
+            Some(c) => format!("[{}: {}](/contexts/{})", c.type_name, c.name, c.id),
+            None => format!("[{}](/contexts/{})", id, id),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+async fn get_contexts_by_ids(
+    store: &mut mlmd::MetadataStore,
+    ids: &[i32],
+) -> anyhow::Result<HashMap<i32, Context>> {
+    let mut contexts = Vec::new();
+    for ids in ids.chunks(list::BATCH_SIZE) {
+        contexts.extend(
+            store
+                .get_contexts()
+                .ids(ids.iter().copied().map(mlmd::metadata::ContextId::new))
+                .execute()
+                .await?,
+        );
+    }
+    if contexts.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let type_ids = contexts.iter().map(|x| x.type_id).collect::<HashSet<_>>();
+    let types = store
+        .get_context_types()
+        .ids(type_ids.into_iter())
+        .execute()
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
+    Ok(contexts
+        .into_iter()
+        .filter_map(|x| {
+            let ty = types.get(&x.type_id)?.clone();
+            Some((x.id.get(), Context::from((ty, x))))
+        })
+        .collect())
+}
@@ -0,0 +1,428 @@
+use crate::graph::{BatchStore, Graph, Node, NodeId};
+use crate::mlmd::artifact::Artifact;
+use crate::mlmd::context::Context;
+use crate::mlmd::event::Event;
+use crate::mlmd::execution::Execution;
+use crate::mlmd::property::PropertyValue;
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
+use crate::web::{response, Config};
+use actix_web::{get, web, HttpResponse};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DiffQuery {
+    pub a: i32,
+    pub b: i32,
+}
+
+/// Node of a lineage snapshot to be compared.
+#[derive(Debug, Clone)]
+struct DiffNode {
+    id: i32,
+    label: String,
+    url: String,
+    properties: BTreeMap<String, PropertyValue>,
+}
+
+impl DiffNode {
+    fn properties(
+        properties: &BTreeMap<String, PropertyValue>,
+        custom_properties: &BTreeMap<String, PropertyValue>,
+    ) -> BTreeMap<String, PropertyValue> {
+        properties
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .chain(
+                custom_properties
+                    .iter()
+                    .map(|(k, v)| (format!("{} (custom)", k), v.clone())),
+            )
+            .collect()
+    }
+
+    fn execution(x: &Execution) -> Self {
+        Self {
+            id: x.id,
+            label: format!(
+                "{} {}",
+                x.type_name,
+                x.name.as_ref().map_or("", |x| x.as_str())
+            ),
+            url: format!("/executions/{}", x.id),
+            properties: Self::properties(&x.properties, &x.custom_properties),
+        }
+    }
+
+    fn artifact(x: &Artifact) -> Self {
+        Self {
+            id: x.id,
+            label: format!(
+                "{} {}",
+                x.type_name,
+                x.name.as_ref().map_or("", |x| x.as_str())
+            ),
+            url: format!("/artifacts/{}", x.id),
+            properties: Self::properties(&x.properties, &x.custom_properties),
+        }
+    }
+
+    fn context(x: &Context) -> Self {
+        Self {
+            id: x.id,
+            label: format!("{} {}", x.type_name, x.name),
+            url: format!("/contexts/{}", x.id),
+            properties: Self::properties(&x.properties, &x.custom_properties),
+        }
+    }
+
+    fn link_md(&self) -> String {
+        format!("[{}]({}) {}", self.id, self.url, self.label.trim())
+    }
+}
+
+/// Nodes of a lineage keyed by their positions (e.g., `< examples < Transform`).
+///
+/// Nodes at the same position in two snapshots are compared with each other.
+#[derive(Debug, Default)]
+struct Snapshot {
+    root: Option<DiffNode>,
+    nodes: BTreeMap<String, DiffNode>,
+}
+
+impl Snapshot {
+    /// Makes the snapshot of the lineage graph of an execution.
+    ///
+    /// Upstream nodes are keyed by the event paths (or the type names of artifacts without paths)
+    /// and the type names of executions from the execution, and outputs are keyed by their event paths.
+    /// Nodes that would have the same key are numbered (e.g., `< Examples #2`).
+    ///
+    /// Returns `None` if there is no such execution.
+    async fn execution(store: &mut mlmd::MetadataStore, id: i32) -> anyhow::Result<Option<Self>> {
+        let executions = store
+            .get_executions()
+            .id(mlmd::metadata::ExecutionId::new(id))
+            .execute()
+            .await?;
+        if executions.is_empty() {
+            return Ok(None);
+        }
+
+        let graph = Graph::new(store, NodeId::Execution(id)).await?;
+        let nodes = graph
+            .nodes
+            .iter()
+            .map(|n| (n.id(), n))
+            .collect::<HashMap<_, _>>();
+
+        let mut snapshot = Self::default();
+        let mut counts = HashMap::new();
+        let mut keys = HashMap::new();
+        let mut queue = std::collections::VecDeque::new();
+        keys.insert(NodeId::Execution(id), String::new());
+        queue.push_back(NodeId::Execution(id));
+        while let Some(curr) = queue.pop_front() {
+            let key = keys[&curr].clone();
+            for edge in graph.edges.iter().filter(|e| e.target == curr) {
+                if keys.contains_key(&edge.source) {
+                    continue;
+                }
+                let step = match nodes.get(&edge.source) {
+                    Some(Node::Execution { node, .. }) => node.type_name.clone(),
+                    Some(Node::Artifact { node, .. }) => {
+                        artifact_step(&edge.event, &node.type_name)
+                    }
+                    None => continue,
+                };
+                let step = format!("{} < {}", key, step).trim().to_owned();
+                keys.insert(edge.source, unique_key(&mut counts, step));
+                queue.push_back(edge.source);
+            }
+        }
+
+        let events = store
+            .get_events()
+            .execution(mlmd::metadata::ExecutionId::new(id))
+            .execute()
+            .await?;
+        for event in events.into_iter().map(Event::from) {
+            let id = NodeId::Artifact(event.artifact_id);
+            if !event.ty.is_output() || keys.contains_key(&id) {
+                continue;
+            }
+            if let Some(Node::Artifact { node, .. }) = nodes.get(&id) {
+                let step = format!("> {}", artifact_step(&event, &node.type_name));
+                keys.insert(id, unique_key(&mut counts, step));
+            }
+        }
+
+        for (node_id, key) in keys {
+            let node = match nodes.get(&node_id) {
+                Some(Node::Execution { node, .. }) => DiffNode::execution(node),
+                Some(Node::Artifact { node, .. }) => DiffNode::artifact(node),
+                None => continue,
+            };
+            if key.is_empty() {
+                snapshot.root = Some(node);
+            } else {
+                snapshot.nodes.insert(key, node);
+            }
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Makes the snapshot of the executions and artifacts in a context (e.g., a pipeline run).
+    ///
+    /// Executions are keyed by their type names and names,
+    /// and artifacts are keyed by the keys of the producing executions and the event paths
+    /// (or the type names of artifacts without paths).
+    /// Nodes that would have the same key are numbered (e.g., `Trainer #2`).
+    ///
+    /// Returns `None` if there is no such context.
+    async fn context(store: &mut mlmd::MetadataStore, id: i32) -> anyhow::Result<Option<Self>> {
+        let context_id = mlmd::metadata::ContextId::new(id);
+        let contexts = store.get_contexts().id(context_id).execute().await?;
+        if contexts.is_empty() {
+            return Ok(None);
+        }
+        let context_types = store
+            .get_context_types()
+            .id(contexts[0].type_id)
+            .execute()
+            .await?;
+        anyhow::ensure!(
+            !context_types.is_empty(),
+            "no such context type: {}",
+            contexts[0].type_id.get()
+        );
+        let context = Context::from((context_types[0].clone(), contexts[0].clone()));
+
+        let executions = store.get_executions().context(context_id).execute().await?;
+        let execution_types = GetExecutionsQuery::default()
+            .get_execution_types(store, &executions)
+            .await?;
+        let mut executions = executions
+            .into_iter()
+            .map(|x| Execution::from((execution_types[&x.type_id].clone(), x)))
+            .collect::<Vec<_>>();
+        executions.sort_by_key(|x| (x.ctime, x.id));
+
+        let artifacts = store.get_artifacts().context(context_id).execute().await?;
+        let artifact_types = GetArtifactsQuery::default()
+            .get_artifact_types(store, &artifacts)
+            .await?;
+        let artifacts = artifacts
+            .into_iter()
+            .map(|x| {
+                let x = Artifact::from((artifact_types[&x.type_id].clone(), x));
+                (x.id, x)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let execution_ids = executions
+            .iter()
+            .map(|x| NodeId::Execution(x.id))
+            .collect::<Vec<_>>();
+        let mut events: HashMap<_, Vec<_>> = HashMap::new();
+        for (id, event) in BatchStore::new(store).get_events(&execution_ids).await? {
+            events.entry(id).or_default().push(Event::from(event));
+        }
+
+        let mut snapshot = Self {
+            root: Some(DiffNode::context(&context)),
+            nodes: BTreeMap::new(),
+        };
+        let mut keyed_artifacts = BTreeSet::new();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for x in &executions {
+            let key = format!(
+                "{} {}",
+                x.type_name,
+                x.name.as_ref().map_or("", |x| x.as_str())
+            )
+            .trim()
+            .to_owned();
+            let key = unique_key(&mut counts, key);
+
+            for event in events.get(&NodeId::Execution(x.id)).into_iter().flatten() {
+                if !event.ty.is_output() || keyed_artifacts.contains(&event.artifact_id) {
+                    continue;
+                }
+                if let Some(a) = artifacts.get(&event.artifact_id) {
+                    keyed_artifacts.insert(a.id);
+                    let step = format!("{} > {}", key, artifact_step(event, &a.type_name));
+                    snapshot
+                        .nodes
+                        .insert(unique_key(&mut counts, step), DiffNode::artifact(a));
+                }
+            }
+            snapshot.nodes.insert(key, DiffNode::execution(x));
+        }
+
+        // Artifacts that are not produced in the context (e.g., imported datasets).
+        for a in artifacts.values() {
+            if keyed_artifacts.contains(&a.id) {
+                continue;
+            }
+            let key = format!(
+                "< {} {}",
+                a.type_name,
+                a.name
+                    .as_ref()
+                    .or(a.uri.as_ref())
+                    .map_or("", |x| x.as_str())
+            );
+            let key = unique_key(&mut counts, key.trim().to_owned());
+            snapshot.nodes.insert(key, DiffNode::artifact(a));
+        }
+        Ok(Some(snapshot))
+    }
+}
+
+/// Returns the event path of an artifact, or its type name if the path is empty.
+fn artifact_step(event: &Event, type_name: &str) -> String {
+    Some(event.path_string())
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| type_name.to_owned())
+}
+
+/// Makes `key` unique among the keys counted in `counts` by numbering the second and later ones.
+fn unique_key(counts: &mut HashMap<String, usize>, key: String) -> String {
+    let count = counts.entry(key.clone()).or_default();
+    *count += 1;
+    if *count > 1 {
+        format!("{} #{}", key, count)
+    } else {
+        key
+    }
+}
+
+/// Makes the markdown of the differences of the properties of `a` and `b`.
+fn property_diffs_md(a: &DiffNode, b: &DiffNode) -> Vec<String> {
+    let keys = a
+        .properties
+        .keys()
+        .chain(b.properties.keys())
+        .collect::<BTreeSet<_>>();
+    keys.into_iter()
+        .filter_map(|k| {
+            let va = a.properties.get(k);
+            let vb = b.properties.get(k);
+            if va == vb {
+                return None;
+            }
+            let show = |v: Option<&PropertyValue>| v.map_or("-".to_owned(), |v| v.to_string());
+            Some(format!("**{}**: {} → {}", k, show(va), show(vb)))
+        })
+        .collect()
+}
+
+fn diff_md(title: &str, a: &Snapshot, b: &Snapshot) -> String {
+    let mut md = format!("# {}\n", title);
+
+    if let (Some(ra), Some(rb)) = (&a.root, &b.root) {
+        md += &format!("- **A**: {}\n", ra.link_md());
+        md += &format!("- **B**: {}\n", rb.link_md());
+
+        let diffs = property_diffs_md(ra, rb);
+        md += "\n## Properties\n\n";
+        if diffs.is_empty() {
+            md += "No differences.\n";
+        }
+        for d in diffs {
+            md += &format!("- {}\n", d);
+        }
+    }
+
+    let keys = a
+        .nodes
+        .keys()
+        .chain(b.nodes.keys())
+        .collect::<BTreeSet<_>>();
+    let mut rows = Vec::new();
+    let (mut n_changed, mut n_added, mut n_removed) = (0, 0, 0);
+    for key in keys {
+        let (status, diffs) = match (a.nodes.get(key), b.nodes.get(key)) {
+            (Some(x), Some(y)) if x.id == y.id => ("same", Vec::new()),
+            (Some(x), Some(y)) => {
+                n_changed += 1;
+                ("**changed**", property_diffs_md(x, y))
+            }
+            (None, Some(_)) => {
+                n_added += 1;
+                ("**added**", Vec::new())
+            }
+            (Some(_), None) => {
+                n_removed += 1;
+                ("**removed**", Vec::new())
+            }
+            (None, None) => unreachable!(),
+        };
+        rows.push(format!(
+            "| {} | {} | {} | {} | {} |\n",
+            key,
+            a.nodes.get(key).map_or(String::new(), |x| x.link_md()),
+            b.nodes.get(key).map_or(String::new(), |x| x.link_md()),
+            status,
+            diffs.join("<br>")
+        ));
+    }
+
+    md += &format!(
+        "\n## Lineage\n\n- changed: {}\n- added: {}\n- removed: {}\n\n",
+        n_changed, n_added, n_removed
+    );
+    md += "| lineage | A | B | status | property differences |\n";
+    md += "|---------|---|---|--------|----------------------|\n";
+    for row in rows {
+        md += &row;
+    }
+    md
+}
+
+/// Makes the markdown of the form to diff `id` with another item.
+pub fn diff_form_md(path: &str, id: i32) -> String {
+    format!(
+        "- **Diff**: <form style='display: inline' action='{}'><input type='hidden' name='a' value='{}'><input type='number' name='b' placeholder='ID' required><button>Diff</button></form>\n",
+        path, id
+    )
+}
+
+#[get("/diff/executions")]
+pub async fn diff_executions(
+    config: web::Data<Config>,
+    query: web::Query<DiffQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
+    let a = Snapshot::execution(&mut store, query.a)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(format!("no such execution: {}", query.a))
+        })?;
+    let b = Snapshot::execution(&mut store, query.b)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| {
+            actix_web::error::ErrorNotFound(format!("no such execution: {}", query.b))
+        })?;
+    Ok(response::markdown(&diff_md("Execution Diff", &a, &b)))
+}
+
+#[get("/diff/contexts")]
+pub async fn diff_contexts(
+    config: web::Data<Config>,
+    query: web::Query<DiffQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
+    let a = Snapshot::context(&mut store, query.a)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound(format!("no such context: {}", query.a)))?;
+    let b = Snapshot::context(&mut store, query.b)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound(format!("no such context: {}", query.b)))?;
+    Ok(response::markdown(&diff_md("Context Diff", &a, &b)))
+}
@@ -1,78 +1,55 @@
-use crate::mlmd::event::Event;
-use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use crate::mlmd::event::{Event, EventOrderByField};
+use crate::web::query::ListQuery;
+use crate::web::{export, pager, response, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use futures::Stream;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct GetEventsQuery {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub artifact: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub execution: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
-    #[serde(default)]
-    pub asc: bool,
-}
+pub type GetEventsQuery = ListQuery<EventOrderByField>;
 
 impl GetEventsQuery {
-    pub fn limit(&self) -> usize {
-        self.limit.unwrap_or(100)
-    }
-
-    pub fn offset(&self) -> usize {
-        self.offset.unwrap_or(0)
-    }
-
-    fn prev(&self) -> Self {
-        let mut this = self.clone();
-        this.offset = Some(self.offset().saturating_sub(self.limit()));
-        this
-    }
-
-    fn next(&self) -> Self {
-        let mut this = self.clone();
-        this.offset = Some(self.offset() + self.limit());
-        this
-    }
-
-    pub fn order_by_ctime(&self, asc: bool) -> Self {
-        let mut this = self.clone();
-        this.asc = asc;
-        this.offset = None;
-        this
-    }
-
-    pub fn execution(&self, id: i32) -> Self {
-        let mut this = self.clone();
-        this.execution = Some(id);
-        this.offset = None;
-        this
-    }
-
-    pub fn artifact(&self, id: i32) -> Self {
-        let mut this = self.clone();
-        this.artifact = Some(id);
-        this.offset = None;
-        this
-    }
-
-    pub fn to_url(&self) -> String {
-        let mut s = format!(
-            "/events/?limit={}&offset={}&asc={}",
-            self.limit(),
-            self.offset(),
-            self.asc
-        );
+    fn request<'a>(
+        &self,
+        store: &'a mut mlmd::MetadataStore,
+    ) -> mlmd::requests::GetEventsRequest<'a> {
+        let mut request = store.get_events();
         if let Some(x) = self.artifact {
-            s += &format!("&artifact={}", x);
+            request = request.artifact(mlmd::metadata::ArtifactId::new(x));
         }
         if let Some(x) = self.execution {
-            s += &format!("&execution={}", x);
+            request = request.execution(mlmd::metadata::ExecutionId::new(x));
         }
-        s
+        request
+    }
+
+    /// Gets all the events matching this query page by page regardless of the pagination parameters.
+    pub fn get_all_events(
+        &self,
+        store: mlmd::MetadataStore,
+    ) -> impl Stream<Item = anyhow::Result<Vec<Event>>> {
+        let query = self.clone();
+        futures::stream::try_unfold((Some(0), store), move |(offset, mut store)| {
+            let query = query.clone();
+            async move {
+                let offset = match offset {
+                    Some(offset) => offset,
+                    None => return Ok(None),
+                };
+                let batch = query
+                    .request(&mut store)
+                    .limit(export::BATCH_SIZE)
+                    .offset(offset)
+                    .order_by(query.order_by.into(), query.asc)
+                    .execute()
+                    .await?;
+                let next = if batch.len() < export::BATCH_SIZE {
+                    None
+                } else {
+                    Some(offset + batch.len())
+                };
+                let events = batch.into_iter().map(Event::from).collect();
+                Ok(Some((events, (next, store))))
+            }
+        })
     }
 }
 
@@ -80,47 +57,63 @@ impl GetEventsQuery {
 async fn get_events(
     config: web::Data<Config>,
     query: web::Query<GetEventsQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let mut store = config.connect_metadata_store().await?;
-    let mut request = store
-        .get_events()
+    let events = query
+        .request(&mut store)
         .limit(query.limit())
         .offset(query.offset())
-        .order_by(mlmd::requests::EventOrderByField::CreateTime, query.asc);
-
-    if let Some(x) = query.artifact {
-        request = request.artifact(mlmd::metadata::ArtifactId::new(x));
-    }
-    if let Some(x) = query.execution {
-        request = request.execution(mlmd::metadata::ExecutionId::new(x));
-    }
-
-    let events = request
+        .order_by(query.order_by.into(), query.asc)
         .execute()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
+    let total = query
+        .request(&mut store)
+        .count()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut md = "# Events\n".to_string();
 
-    if query.offset() != 0 {
-        md += &format!(" [<<]({})", query.prev().to_url());
-    } else {
-        md += " <<";
-    }
-    md += &format!(" {}~{} ", query.offset() + 1, query.offset() + events.len());
-    if events.len() == query.limit() {
-        md += &format!("[>>]({})", query.next().to_url());
-    } else {
-        md += ">>";
-    }
+    md += &pager::pager_md(
+        query.offset(),
+        query.limit(),
+        events.len(),
+        total,
+        (
+            &query
+                .page(query.offset().saturating_sub(query.limit()))
+                .to_url(),
+            &query.page(query.offset() + query.limit()).to_url(),
+        ),
+        |offset| query.page(offset).to_url(),
+        |limit| query.per_page(limit).to_url(),
+    );
+    md += ",\n";
+    md += &export::export_links_md(&query);
+    md += " | ";
+    md += &views::save_link_md(&req);
+    md += "\n";
 
     md += "\n";
     md += &format!(
         "| execution | artifact | type | path | time{} |\n",
         if query.asc {
-            format!("<[>]({})", query.order_by_ctime(!query.asc).to_url())
+            format!(
+                "<[>]({})",
+                query
+                    .order_by(EventOrderByField::CreateTime, !query.asc)
+                    .to_url()
+            )
         } else {
-            format!("[<]({})>", query.order_by_ctime(!query.asc).to_url())
+            format!(
+                "[<]({})>",
+                query
+                    .order_by(EventOrderByField::CreateTime, !query.asc)
+                    .to_url()
+            )
         }
     );
     md += "|-----------|----------|------|------|------|\n";
@@ -130,10 +123,10 @@ async fn get_events(
         md += &format!(
             "| [{}]({}) [@](/executions/{}) | [{}]({}) [@](/artifacts/{}) | {} | {} | {} | \n",
             event.execution_id,
-            query.execution(event.execution_id).to_url(),
+            query.filter_execution(event.execution_id).to_url(),
             event.execution_id,
             event.artifact_id,
-            query.artifact(event.artifact_id).to_url(),
+            query.filter_artifact(event.artifact_id).to_url(),
             event.artifact_id,
             event.ty,
             event
@@ -142,7 +135,7 @@ async fn get_events(
                 .map(|x| x.to_string())
                 .collect::<Vec<_>>()
                 .join(","),
-            event.time,
+            tz.convert(event.time),
         );
     }
 
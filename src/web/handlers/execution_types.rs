@@ -1,7 +1,13 @@
-use crate::mlmd::execution::{ExecutionTypeDetail, ExecutionTypeSummary};
+use crate::graph::NodeId;
+use crate::mlmd::execution::{
+    Execution, ExecutionOrderByField, ExecutionTypeDetail, ExecutionTypeSummary,
+};
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
 use crate::web::link::Link;
+use crate::web::type_stats::{self, TypeStats};
 use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 
 #[get("/execution_types/")]
 async fn get_execution_type_summaries(
@@ -17,6 +23,8 @@ async fn get_execution_type_summaries(
     let mut md = concat!(
         "# Execution Types\n",
         "\n",
+        "[Type Graph](/types/graph)\n",
+        "\n",
         "| id | name | properties |\n",
         "|----|------|------------|\n"
     )
@@ -39,9 +47,12 @@ async fn get_execution_type_summaries(
 async fn get_execution_type_detail(
     config: web::Data<Config>,
     path: web::Path<(i32,)>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let id = path.0;
     let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
 
     let types = store
         .get_execution_types()
@@ -61,12 +72,83 @@ async fn get_execution_type_detail(
 
     md += &format!("- ID: {}\n", ty.id);
     md += &format!("- Name: {}\n", ty.name);
-    md += &format!("- Properties:\n");
+    md += "- Properties:\n";
 
     for (k, v) in &ty.properties {
         md += &format!("  - {}: {}\n", k, v);
     }
-    md += &format!("- [Executions](/executions/?type={})\n", ty.name); // TODO: escape
+    let query = GetExecutionsQuery::default().filter_type(&ty.name);
+    let total = query
+        .count_executions(&mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    md += &format!("- [Executions]({}) ({})\n", query.to_url(), total);
+
+    let executions = query
+        .order_by(ExecutionOrderByField::UpdateTime, false)
+        .per_page(type_stats::SAMPLE_LIMIT)
+        .get_executions(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|x| Execution::from((types[0].clone(), x)))
+        .collect::<Vec<_>>();
+
+    let mut stats = TypeStats::new(ty.properties.keys());
+    for x in &executions {
+        stats.add(&x.properties, &x.custom_properties);
+    }
+    md += "\n## Property Statistics\n\n";
+    md += &format!(
+        "Computed from the latest {} of {} executions.\n\n",
+        stats.samples(),
+        total
+    );
+    md += &stats.properties_md();
+
+    if !executions.is_empty() {
+        md += "\n## Recent Executions\n\n";
+        md += "| id | name | state | update-time |\n";
+        md += "|----|------|-------|-------------|\n";
+        for x in executions.iter().take(type_stats::RECENT_LIMIT) {
+            md += &format!(
+                "| [{}](/executions/{}) | {} | {} | {} |\n",
+                x.id,
+                x.id,
+                x.name.as_ref().map_or("", |x| x.as_str()),
+                x.state,
+                tz.convert(x.mtime)
+            );
+        }
+    }
+
+    let nodes = executions
+        .iter()
+        .take(type_stats::EVENT_SAMPLE_LIMIT)
+        .map(|x| NodeId::Execution(x.id))
+        .collect::<Vec<_>>();
+    let counts = type_stats::count_neighbor_types(&mut store, &nodes)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !counts.is_empty() {
+        md += "\n## Input and Output Artifacts\n\n";
+        md += &format!(
+            "Counted from the events of the latest {} executions.\n\n",
+            nodes.len()
+        );
+        md += "| artifact type | inputs | outputs |\n|---------------|--------|---------|\n";
+        for (type_name, count) in counts {
+            md += &format!(
+                "| [{}]({}) | {} | {} |\n",
+                type_name,
+                GetArtifactsQuery::default()
+                    .filter_type(&type_name)
+                    .to_url(),
+                count.consumed,
+                count.produced
+            );
+        }
+    }
 
     Ok(response::markdown(&md))
 }
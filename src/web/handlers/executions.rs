@@ -1,10 +1,17 @@
-use crate::hook::GeneralOutput;
+use crate::graph::{BatchStore, NodeId};
+use crate::hook::{GeneralOutput, ItemType};
+use crate::mlmd::event::{Event, EventType};
 use crate::mlmd::execution::{Execution, ExecutionOrderByField};
-use crate::time::DateTime;
-use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
-use std::collections::{HashMap, HashSet};
-use std::time::Duration;
+use crate::mlmd::list::{self, Cursor};
+use crate::web::handlers::lineage::{self, graph_formats_md, GetGraphQuery};
+use crate::web::handlers::plot::{self, PlotTarget};
+use crate::web::handlers::{contexts, diff};
+use crate::web::pager;
+use crate::web::query::{self, ListQuery};
+use crate::web::{export, response, time_range, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use futures::Stream;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[get("/executions/{id}/contents/{name}")]
 async fn get_execution_content(
@@ -55,79 +62,34 @@ async fn get_execution_content(
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct GetExecutionsQuery {
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
-    #[serde(default)]
-    pub order_by: ExecutionOrderByField,
-    #[serde(default)]
-    pub asc: bool,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_start: Option<DateTime>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtime_end: Option<DateTime>,
-}
+pub type GetExecutionsQuery = ListQuery<ExecutionOrderByField>;
 
 impl GetExecutionsQuery {
-    // TODO
-    async fn get_executions(
+    pub async fn get_executions(
         &self,
         store: &mut mlmd::MetadataStore,
+        conn: &mut sqlx::AnyConnection,
     ) -> anyhow::Result<Vec<mlmd::metadata::Execution>> {
-        let context_id = if let Some(context) = self.context {
-            Some(mlmd::metadata::ContextId::new(context))
-        } else {
-            None
-        };
-
-        let mut request = store.get_executions().limit(self.limit.unwrap_or(100));
-        if let Some(c) = context_id {
-            request = request.context(c)
-        }
-        if let Some(n) = self.offset {
-            request = request.offset(n);
-        }
-        if let Some(n) = &self.type_name {
-            if let Some(m) = &self.name {
-                request = request.type_and_name(n, m);
-            } else {
-                request = request.ty(n);
-            }
-        }
-        request = request.order_by(self.order_by.into(), self.asc);
-
-        match (self.mtime_start, self.mtime_end) {
-            (None, None) => {}
-            (Some(start), None) => {
-                request =
-                    request.update_time(Duration::from_millis(start.timestamp_millis() as u64)..);
-            }
-            (None, Some(end)) => {
-                request =
-                    request.update_time(..Duration::from_millis(end.timestamp_millis() as u64));
-            }
-            (Some(start), Some(end)) => {
-                request = request.update_time(
-                    Duration::from_millis(start.timestamp_millis() as u64)
-                        ..Duration::from_millis(end.timestamp_millis() as u64),
-                );
-            }
+        let ids = self.get_item_ids(conn, ItemType::Execution).await?;
+        let mut executions = Vec::new();
+        for ids in ids.chunks(list::BATCH_SIZE) {
+            executions.extend(
+                store
+                    .get_executions()
+                    .ids(ids.iter().copied().map(mlmd::metadata::ExecutionId::new))
+                    .execute()
+                    .await?,
+            );
         }
+        query::sort_by_ids(&mut executions, &ids, |x| x.id.get());
+        Ok(executions)
+    }
 
-        Ok(request.execute().await?)
+    pub async fn count_executions(&self, conn: &mut sqlx::AnyConnection) -> anyhow::Result<usize> {
+        self.count_items(conn, ItemType::Execution).await
     }
 
-    async fn get_execution_types(
+    pub async fn get_execution_types(
         &self,
         store: &mut mlmd::MetadataStore,
         executions: &[mlmd::metadata::Execution],
@@ -143,86 +105,89 @@ impl GetExecutionsQuery {
             .collect())
     }
 
-    fn prev(&self) -> Self {
-        let mut this = self.clone();
-        this.offset = Some(
-            self.offset
-                .unwrap_or(0)
-                .saturating_sub(self.limit.unwrap_or(100)),
-        );
-        this
-    }
-
-    fn next(&self) -> Self {
+    /// Gets all the executions matching this query page by page regardless of the pagination parameters.
+    pub fn get_all_executions(
+        &self,
+        store: mlmd::MetadataStore,
+        conn: sqlx::AnyConnection,
+    ) -> impl Stream<Item = anyhow::Result<Vec<Execution>>> {
+        let query = Some(self.all().per_page(export::BATCH_SIZE));
+        futures::stream::try_unfold(
+            (query, store, conn),
+            |(query, mut store, mut conn)| async move {
+                let query = match query {
+                    Some(query) => query,
+                    None => return Ok(None),
+                };
+                let batch = query.get_executions(&mut store, &mut conn).await?;
+                let types = query.get_execution_types(&mut store, &batch).await?;
+                let next = if batch.len() < query.limit() {
+                    None
+                } else {
+                    Some(query.next(&batch))
+                };
+                let executions = batch
+                    .into_iter()
+                    .map(|x| Execution::from((types[&x.type_id].clone(), x)))
+                    .collect();
+                Ok(Some((executions, (next, store, conn))))
+            },
+        )
+    }
+
+    fn next(&self, executions: &[mlmd::metadata::Execution]) -> Self {
         let mut this = self.clone();
         this.offset = Some(self.offset() + self.limit());
+        this.cursor = executions.last().map(|x| self.cursor(x));
+        this.before = None;
         this
     }
 
-    fn reset_mtime_start(&self) -> Self {
-        let mut this = self.clone();
-        this.mtime_start = None;
-        this.offset = None;
-        this
-    }
-
-    fn reset_mtime_end(&self) -> Self {
-        let mut this = self.clone();
-        this.mtime_end = None;
-        this.offset = None;
-        this
-    }
-
-    fn filter_type(&self, type_name: &str) -> Self {
-        let mut this = self.clone();
-        this.type_name = Some(type_name.to_owned());
-        this.offset = None;
-        this
-    }
-
-    fn order_by(&self, field: ExecutionOrderByField, asc: bool) -> Self {
+    fn prev(&self, executions: &[mlmd::metadata::Execution]) -> Self {
         let mut this = self.clone();
-        this.order_by = field;
-        this.asc = asc;
-        this.offset = None;
+        let offset = self.offset().saturating_sub(self.limit());
+        this.offset = Some(offset);
+        this.cursor = None;
+        // The first page is cheap to get by the offset, which also avoids a cursor of an empty page.
+        this.before = if offset == 0 {
+            None
+        } else {
+            executions.first().map(|x| self.cursor(x))
+        };
         this
     }
 
-    fn to_url(&self) -> String {
-        let qs = serde_json::to_value(self)
-            .expect("unreachable")
-            .as_object()
-            .expect("unwrap")
-            .into_iter()
-            .map(|(k, v)| {
-                format!(
-                    "{}={}",
-                    k,
-                    v.to_string().trim_matches('"').replace('+', "%2B") // TODO: escape
-                )
-            })
-            .collect::<Vec<_>>();
-        format!("/executions/?{}", qs.join("&"))
-    }
-
-    fn offset(&self) -> usize {
-        self.offset.unwrap_or(0)
-    }
-
-    fn limit(&self) -> usize {
-        self.limit.unwrap_or(100)
+    fn cursor(&self, x: &mlmd::metadata::Execution) -> Cursor {
+        let id = x.id.get();
+        match self.order_by {
+            ExecutionOrderByField::Id => Cursor::id(id),
+            ExecutionOrderByField::Name => Cursor::name(id, x.name.as_deref()),
+            ExecutionOrderByField::CreateTime => Cursor::time(id, x.create_time_since_epoch),
+            ExecutionOrderByField::UpdateTime => Cursor::time(id, x.last_update_time_since_epoch),
+        }
     }
 }
 
 #[get("/executions/")]
 pub async fn get_executions(
     config: web::Data<Config>,
-    query: web::Query<GetExecutionsQuery>,
+    mut query: web::Query<GetExecutionsQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    query
+        .resolve_context_filters(&mut conn, ItemType::Execution)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let executions = query
-        .get_executions(&mut store)
+        .get_executions(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let total = query
+        .count_executions(&mut conn)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
     let execution_types = query
@@ -231,61 +196,43 @@ pub async fn get_executions(
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut md = "# Executions\n".to_string();
-    let mut pager_md = String::new();
-    if query.offset() != 0 {
-        pager_md += &format!(" [<<]({})", query.prev().to_url());
-    } else {
-        pager_md += " <<";
-    }
-    pager_md += &format!(
-        " {}~{} ",
-        query.offset() + 1,
-        query.offset() + executions.len()
+    let pager_md = pager::pager_md(
+        query.offset(),
+        query.limit(),
+        executions.len(),
+        total,
+        (
+            &query.prev(&executions).to_url(),
+            &query.next(&executions).to_url(),
+        ),
+        |offset| query.page(offset).to_url(),
+        |limit| query.per_page(limit).to_url(),
     );
-    if executions.len() == query.limit() {
-        pager_md += &format!("[>>]({})", query.next().to_url());
-    } else {
-        pager_md += ">>";
-    }
 
     md += &pager_md;
-    md += &format!(
-        r#",
-Update Time: <input type="date" id="start_date" {} onchange="filter_start_date()"> ~
-             <input type="date" id="end_date" {} onchange="filter_end_date()">
-
-<script type="text/javascript">
-function filter_start_date() {{
-  var v = document.getElementById("start_date").value;
-  location.href = "{}&mtime-start=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-<script type="text/javascript">
-function filter_end_date() {{
-  var v = document.getElementById("end_date").value;
-  location.href = "{}&mtime-end=" + v + "T00:00:00%2B09:00";
-}}
-</script>
-"#,
-        if let Some(v) = &query.mtime_start {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        if let Some(v) = &query.mtime_end {
-            format!("value={:?}", v.format("%Y-%m-%d").to_string())
-        } else {
-            "".to_owned()
-        },
-        query.reset_mtime_start().to_url(),
-        query.reset_mtime_end().to_url()
+    md += ",\n";
+    md += &time_range::mtime_range_filter(
+        tz,
+        query.mtime_start,
+        query.mtime_end,
+        query.mtime_last,
+        |start, end| query.mtime_range(start, end).to_url(),
+        |period| query.mtime_last(period).to_url(),
     );
+    md += "\n";
+    md += &export::export_links_md(&query);
+    md += " | ";
+    md += &plot::plot_links_md(PlotTarget::Executions(query.0.clone()));
+    md += " | ";
+    md += &views::save_link_md(&req);
+    md += &contexts::context_filters_md(&query);
+    md += "\n";
 
     md += "\n";
     md += &format!(
         "| id{}{} | type | name{}{} | state | update-time{}{} | summary |\n",
         if query.order_by == ExecutionOrderByField::Id && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -293,7 +240,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ExecutionOrderByField::Id && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -301,7 +248,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ExecutionOrderByField::Name && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -309,7 +256,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ExecutionOrderByField::Name && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -317,7 +264,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ExecutionOrderByField::UpdateTime && query.asc {
-            format!("<")
+            "<".to_string()
         } else {
             format!(
                 "[<]({})",
@@ -327,7 +274,7 @@ function filter_end_date() {{
             )
         },
         if query.order_by == ExecutionOrderByField::UpdateTime && !query.asc {
-            format!(">")
+            ">".to_string()
         } else {
             format!(
                 "[>]({})",
@@ -349,14 +296,14 @@ function filter_end_date() {{
         .await?;
     for a in executions {
         md += &format!(
-            "| [{}]({}) | [{}]({}) | {} | {} | {} | {} |\n",
+            "| [{}](/executions/{}) | [{}]({}) | {} | {} | {} | {} |\n",
+            a.id,
             a.id,
-            format!("/executions/{}", a.id),
             a.type_name,
             query.filter_type(&a.type_name).to_url(),
             a.name.as_ref().map_or("", |x| x.as_str()),
             a.state,
-            a.mtime,
+            tz.convert(a.mtime),
             a.summary.as_ref().map_or("", |x| x.as_str())
         );
     }
@@ -378,7 +325,9 @@ pub async fn get_execution(
     config: web::Data<Config>,
     path: web::Path<(String,)>,
     query: web::Query<GetExecutionQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
     let id_or_name = &path.0;
     let mut store = config.connect_metadata_store().await?;
 
@@ -399,9 +348,9 @@ pub async fn get_execution(
                     .await
                     .map_err(actix_web::error::ErrorInternalServerError)?
             } else {
-                return Err(actix_web::error::ErrorBadRequest(format!(
-                    "`type` query parameter must be specified"
-                )));
+                return Err(actix_web::error::ErrorBadRequest(
+                    "`type` query parameter must be specified",
+                ));
             }
         }
     };
@@ -443,370 +392,144 @@ pub async fn get_execution(
         md += &format!("- **Name**: {}\n", x);
     }
     md += &format!("- **State**: {}\n", execution.state);
-    md += &format!("- **Create Time**: {}\n", execution.ctime);
-    md += &format!("- **Update Time**: {}\n", execution.mtime);
+    md += &format!("- **Create Time**: {}\n", tz.convert(execution.ctime));
+    md += &format!("- **Update Time**: {}\n", tz.convert(execution.mtime));
 
     if !execution.properties.is_empty() {
-        md += &format!("- **Properties**:\n");
+        md += "- **Properties**:\n";
         for (k, v) in &execution.properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
     if !execution.custom_properties.is_empty() {
-        md += &format!("- **Custom Properties**:\n");
+        md += "- **Custom Properties**:\n";
         for (k, v) in &execution.custom_properties {
             md += &format!("  - **{}**: {}\n", k, v);
         }
     }
 
-    let contexts_len = store
+    let contexts = store
         .get_contexts()
         .execution(mlmd::metadata::ExecutionId::new(execution.id))
-        .count()
+        .execute()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
-    let events_len = store
+    let events = store
         .get_events()
         .execution(mlmd::metadata::ExecutionId::new(execution.id))
-        .count()
+        .execute()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
-    if contexts_len > 0 {
+    if !contexts.is_empty() {
+        let contexts_len = contexts.len();
+        let links = contexts::context_links_md(&mut store, contexts)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
         md += &format!(
-            "- [**Contexts**](/contexts/?execution={}) ({})\n",
-            execution.id, contexts_len
+            "- [**Contexts**](/contexts/?execution={}) ({}): {}\n",
+            execution.id, contexts_len, links
         );
     }
-    if events_len > 0 {
+    if !events.is_empty() {
         md += &format!(
             "- [**Events**](/events/?execution={}) ({})\n",
-            execution.id, events_len
+            execution.id,
+            events.len()
         );
     }
 
-    md += &format!("- [**Graph**](/executions/{}/graph)\n", execution.id);
+    md += &format!(
+        "- [**Graph**](/executions/{}/graph) ([explore](/lineage/explorer?node=E{}); {})\n",
+        execution.id,
+        execution.id,
+        graph_formats_md(&format!("/executions/{}/graph", execution.id))
+    );
+    md += &diff::diff_form_md("/diff/executions", execution.id);
+
+    md += &event_artifacts_md(&config, &mut store, events).await?;
 
     Ok(response::markdown(&md))
 }
 
-#[get("/executions/{id}/graph")]
-pub async fn get_execution_graph(
-    config: web::Data<Config>,
-    path: web::Path<(i32,)>,
-) -> actix_web::Result<HttpResponse> {
-    let id = path.0;
-    let mut store = config.connect_metadata_store().await?;
+/// Makes the markdown of the input and output artifacts of an execution, grouped by event type.
+async fn event_artifacts_md(
+    config: &Config,
+    store: &mut mlmd::MetadataStore,
+    events: Vec<mlmd::metadata::Event>,
+) -> actix_web::Result<String> {
+    if events.is_empty() {
+        return Ok(String::new());
+    }
 
-    let graph = Graph::new(&mut store, id)
+    let ids = events
+        .iter()
+        .map(|e| e.artifact_id.get())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let artifacts = BatchStore::new(store)
+        .get_artifacts(&ids)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
+    let artifacts = config
+        .hook_runner
+        .run_artifact_summary_hook(artifacts)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
 
-    let svg = std::process::Command::new("dot")
-        .arg("-Tsvg")
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .ok()
-        .and_then(|mut child| {
-            use std::io::Write;
-
-            let writer = child.stdin.as_mut()?;
-            graph.render(writer).ok()?;
-            writer.flush().ok()?;
-            let output = child.wait_with_output().ok()?;
-            if !output.status.success() {
-                None
-            } else {
-                Some(output.stdout)
-            }
-        });
-
-    if let Some(svg) = svg {
-        Ok(response::svg(&String::from_utf8(svg).expect("TODO")))
-    } else {
-        let mut buf = Vec::new();
-        graph.render(&mut buf).expect("TODO");
-        Ok(response::markdown(&String::from_utf8(buf).expect("TODO")))
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum NodeId {
-    Execution(i32),
-    Artifact(i32),
-}
-
-impl std::fmt::Display for NodeId {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            Self::Execution(x) => write!(f, "E{}", x),
-            Self::Artifact(x) => write!(f, "A{}", x),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub enum Node {
-    Execution {
-        node: Execution,
-        inputs: usize,
-        outputs: usize,
-    },
-    Artifact {
-        node: crate::mlmd::artifact::Artifact,
-        inputs: usize,
-        outputs: usize,
-    },
-}
-
-impl Node {
-    pub fn id(&self) -> NodeId {
-        match self {
-            Self::Execution { node, .. } => NodeId::Execution(node.id),
-            Self::Artifact { node, .. } => NodeId::Artifact(node.id),
-        }
-    }
-
-    pub fn set_in_out(&mut self, events: &[mlmd::metadata::Event]) {
-        use mlmd::metadata::EventType::*;
-
-        let mut n_input = 0;
-        let mut n_output = 0;
-        for event in events {
-            match event.ty {
-                Input | DeclaredInput | InternalInput => {
-                    n_input += 1;
-                }
-                Output | DeclaredOutput | InternalOutput => {
-                    n_output += 1;
-                }
-                _ => {}
-            }
-        }
-        match self {
-            Self::Execution {
-                inputs, outputs, ..
-            } => {
-                *inputs = n_input;
-                *outputs = n_output;
-            }
-            Self::Artifact {
-                inputs, outputs, ..
-            } => {
-                *inputs = n_input;
-                *outputs = n_output;
-            }
-        }
-    }
-
-    pub fn label(&self) -> String {
-        match self {
-            Self::Execution {
-                node,
-                inputs,
-                outputs,
-            } => format!(
-                "{}\n{}\nin={},out={}",
-                node.id, node.type_name, inputs, outputs
-            ),
-            Self::Artifact {
-                node,
-                inputs,
-                outputs,
-            } => format!(
-                "{}\n{}\nin={},out={}",
-                node.id, node.type_name, inputs, outputs
-            ),
-        }
-    }
-
-    pub fn url(&self) -> String {
-        match self {
-            Self::Execution { node, .. } => format!("/executions/{}", node.id),
-            Self::Artifact { node, .. } => format!("/artifacts/{}", node.id),
-        }
-    }
+    let mut events = events.into_iter().map(Event::from).collect::<Vec<_>>();
+    events.sort_by_key(|e| (e.path_string(), e.artifact_id));
 
-    pub fn shape(&self) -> String {
-        match self {
-            Self::Execution { .. } => format!("box"),
-            Self::Artifact { .. } => format!("ellipse"),
+    let mut md = String::new();
+    for &ty in EventType::ALL {
+        let events = events.iter().filter(|e| e.ty == ty).collect::<Vec<_>>();
+        if events.is_empty() {
+            continue;
         }
-    }
-
-    pub fn attrs(&self) -> Vec<String> {
-        vec![
-            format!("label={:?}", self.label()),
-            format!("shape={:?}", self.shape()),
-            format!("URL={:?}", self.url()),
-        ]
-    }
-}
 
-#[derive(Debug, Clone)]
-pub struct Edge {
-    pub source: NodeId,
-    pub target: NodeId,
-    pub event: crate::mlmd::event::Event,
-}
-
-#[derive(Debug)]
-struct Graph {
-    nodes: Vec<Node>,
-    edges: Vec<Edge>,
-}
-
-impl Graph {
-    async fn new(store: &mut mlmd::MetadataStore, execution_id: i32) -> anyhow::Result<Self> {
-        let mut nodes = HashMap::new();
-        let mut edges = Vec::new();
-        let mut stack = vec![NodeId::Execution(execution_id)];
-        while let Some(curr) = stack.pop() {
-            if nodes.contains_key(&curr) {
+        md += &format!(
+            "\n## {} ({})\n\n",
+            if ty.is_input() {
+                "Input Artifacts"
+            } else if ty.is_output() {
+                "Output Artifacts"
+            } else {
+                "Artifacts"
+            },
+            ty
+        );
+        md += "| path | id | type | name | uri | summary |\n";
+        md += "|------|----|------|------|-----|---------|\n";
+        for e in events {
+            let x = if let Some(x) = artifacts.get(&e.artifact_id) {
+                x
+            } else {
                 continue;
-            }
-            let mut curr = match curr {
-                NodeId::Execution(id) => Node::Execution {
-                    node: fetch_execution(store, id).await?,
-                    inputs: 0,
-                    outputs: 0,
-                },
-                NodeId::Artifact(id) => Node::Artifact {
-                    node: fetch_artifact(store, id).await?,
-                    inputs: 0,
-                    outputs: 0,
-                },
-            };
-
-            let events = match &curr {
-                Node::Execution { node, .. } => {
-                    store
-                        .get_events()
-                        .execution(mlmd::metadata::ExecutionId::new(node.id))
-                        .execute()
-                        .await?
-                }
-                Node::Artifact { node, .. } => {
-                    store
-                        .get_events()
-                        .artifact(mlmd::metadata::ArtifactId::new(node.id))
-                        .execute()
-                        .await?
-                }
             };
-            curr.set_in_out(&events);
-            nodes.insert(curr.id(), curr.clone());
-            anyhow::ensure!(
-                nodes.len() < 100,
-                "Too many executions and artifact to visualize"
+            md += &format!(
+                "| {} | [{}](/artifacts/{}) | {} | {} | {} | {} |\n",
+                e.path_string(),
+                x.id,
+                x.id,
+                x.type_name,
+                x.name.as_ref().map_or("", |x| x.as_str()),
+                x.uri.as_ref().map_or("", |x| x.as_str()),
+                x.summary.as_ref().map_or("", |x| x.as_str())
             );
-
-            for event in events {
-                if matches!(curr, Node::Artifact { .. }) {
-                    use mlmd::metadata::EventType::*;
-                    if matches!(event.ty, Output | DeclaredOutput | InternalOutput) {
-                        let id = NodeId::Execution(event.execution_id.get());
-                        stack.push(id);
-                        edges.push(Edge {
-                            source: id,
-                            target: curr.id(),
-                            event: event.into(),
-                        });
-                    }
-                } else {
-                    use mlmd::metadata::EventType::*;
-                    if event.execution_id.get() == execution_id
-                        || matches!(event.ty, Input | DeclaredInput | InternalInput)
-                    {
-                        let id = NodeId::Artifact(event.artifact_id.get());
-                        stack.push(id);
-                        if matches!(event.ty, Input | DeclaredInput | InternalInput) {
-                            edges.push(Edge {
-                                source: id,
-                                target: curr.id(),
-                                event: event.into(),
-                            });
-                        }
-                    }
-                }
-            }
         }
-
-        Ok(Self {
-            nodes: nodes.into_iter().map(|x| x.1).collect(),
-            edges,
-        })
-    }
-
-    fn render<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
-        writeln!(writer, "digraph execution_graph {{")?;
-
-        for node in &self.nodes {
-            writeln!(writer, "{}[{}]", node.id(), node.attrs().join(","))?;
-        }
-
-        for edge in &self.edges {
-            writeln!(
-                writer,
-                "{} -> {} [label={:?}];",
-                edge.source,
-                edge.target,
-                format!("{:?}:{:?}", edge.event.ty, edge.event.path)
-            )?;
-        }
-
-        writeln!(writer, "}}")?;
-        Ok(())
     }
+    Ok(md)
 }
 
-async fn fetch_execution(store: &mut mlmd::MetadataStore, id: i32) -> anyhow::Result<Execution> {
-    let executions = store
-        .get_executions()
-        .id(mlmd::metadata::ExecutionId::new(id))
-        .execute()
-        .await?;
-    anyhow::ensure!(!executions.is_empty(), "no such execution: {}", id);
-
-    let types = store
-        .get_execution_types()
-        .id(executions[0].type_id)
-        .execute()
-        .await?;
-    anyhow::ensure!(
-        !executions.is_empty(),
-        "no such execution tyep: {}",
-        executions[0].type_id.get()
-    );
-
-    Ok(Execution::from((types[0].clone(), executions[0].clone())))
-}
-
-async fn fetch_artifact(
-    store: &mut mlmd::MetadataStore,
-    id: i32,
-) -> anyhow::Result<crate::mlmd::artifact::Artifact> {
-    let artifacts = store
-        .get_artifacts()
-        .id(mlmd::metadata::ArtifactId::new(id))
-        .execute()
-        .await?;
-    anyhow::ensure!(!artifacts.is_empty(), "no such artifact: {}", id);
-
-    let types = store
-        .get_artifact_types()
-        .id(artifacts[0].type_id)
-        .execute()
-        .await?;
-    anyhow::ensure!(
-        !artifacts.is_empty(),
-        "no such artifact tyep: {}",
-        artifacts[0].type_id.get()
-    );
-
-    Ok(crate::mlmd::artifact::Artifact::from((
-        types[0].clone(),
-        artifacts[0].clone(),
-    )))
+#[get("/executions/{id}/graph")]
+pub async fn get_execution_graph(
+    config: web::Data<Config>,
+    path: web::Path<(i32,)>,
+    query: web::Query<GetGraphQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let graph = lineage::get_graph(&config, NodeId::Execution(path.0)).await?;
+    lineage::graph_response(&graph, config.graph_style(), query.format)
 }
@@ -0,0 +1,71 @@
+use crate::hook::ItemType;
+use crate::web::export::{self, ExportQuery};
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::contexts::GetContextsQuery;
+use crate::web::handlers::events::GetEventsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
+use crate::web::Config;
+use actix_web::{get, web, HttpResponse};
+
+#[get("/export/artifacts/")]
+async fn export_artifacts(
+    config: web::Data<Config>,
+    mut query: web::Query<GetArtifactsQuery>,
+    export_query: web::Query<ExportQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    query
+        .resolve_context_filters(&mut conn, ItemType::Artifact)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let artifacts = query.get_all_artifacts(store, conn);
+    export::download(export_query.format, "artifacts", artifacts)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+#[get("/export/executions/")]
+async fn export_executions(
+    config: web::Data<Config>,
+    mut query: web::Query<GetExecutionsQuery>,
+    export_query: web::Query<ExportQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    query
+        .resolve_context_filters(&mut conn, ItemType::Execution)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let executions = query.get_all_executions(store, conn);
+    export::download(export_query.format, "executions", executions)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+#[get("/export/contexts/")]
+async fn export_contexts(
+    config: web::Data<Config>,
+    query: web::Query<GetContextsQuery>,
+    export_query: web::Query<ExportQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let store = config.connect_metadata_store().await?;
+    let conn = config.connect_database().await?;
+    let contexts = query.get_all_contexts(store, conn);
+    export::download(export_query.format, "contexts", contexts)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+#[get("/export/events/")]
+async fn export_events(
+    config: web::Data<Config>,
+    query: web::Query<GetEventsQuery>,
+    export_query: web::Query<ExportQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let store = config.connect_metadata_store().await?;
+    let events = query.get_all_events(store);
+    export::download(export_query.format, "events", events)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
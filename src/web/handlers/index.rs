@@ -1,18 +1,375 @@
+use crate::hook::ItemType;
+use crate::mlmd::artifact::{ArtifactOrderByField, ArtifactState};
+use crate::mlmd::context::ContextOrderByField;
+use crate::mlmd::execution::{ExecutionOrderByField, ExecutionState};
+use crate::mlmd::stats;
+use crate::time::{datetime_to_duration, DateTime};
+use crate::web::handlers::artifacts::GetArtifactsQuery;
+use crate::web::handlers::contexts::GetContextsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
+use crate::web::handlers::views;
+use crate::web::query::{ListQuery, OrderByField};
 use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use std::collections::{HashMap, HashSet};
+
+const LARGEST_CONTEXTS_LIMIT: usize = 10;
+
+/// Maximum number of days of the recent activity.
+const MAX_DAYS: u32 = 90;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetIndexQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+}
+
+impl GetIndexQuery {
+    fn days(&self) -> i64 {
+        i64::from(self.days.unwrap_or(7).clamp(1, MAX_DAYS))
+    }
+}
 
 #[get("/")]
-async fn get_index(_config: web::Data<Config>) -> actix_web::Result<HttpResponse> {
-    let md = r#"
+async fn get_index(
+    config: web::Data<Config>,
+    query: web::Query<GetIndexQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+
+    let today = tz.today();
+    let since = today - chrono::Duration::days(query.days() - 1);
+
+    let mut md = r#"
 # ml-metadata web viewer
 
 - [Artifacts](/artifacts/)
 - [Artifact Types](/artifact_types/)
 - [Executions](/executions/)
-- [Executions Types](/execution_types/)
+- [Execution Types](/execution_types/)
 - [Contexts](/contexts/)
-- [Context Typess](/context_types/)
+- [Context Types](/context_types/)
+- [Type Graph](/types/graph)
 - [Events](/events/)
-"#;
-    Ok(response::markdown(md))
+- [Timeline](/timeline/)
+- [Saved Views](/views/)
+"#
+    .to_string();
+    md += &views::views_md(&config);
+
+    md += &format!(
+        "\n## Overview (updated since {})\n",
+        tz.convert(since).format("%Y-%m-%d")
+    );
+    md += &format!(
+        "\nPeriod: {}\n",
+        [1, 7, 30, 90]
+            .iter()
+            .map(|d| format!("[{}d](/?days={})", d, d))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    md += "\n| item | total | updated |\n";
+    md += "|------|-------|---------|\n";
+    md += &format!(
+        "| [Artifacts](/artifacts/) | {} | [{}]({}) |\n",
+        store
+            .get_artifacts()
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        store
+            .get_artifacts()
+            .update_time(datetime_to_duration(since)..)
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        period_url(ArtifactOrderByField::UpdateTime, "", since, None)
+    );
+    md += &format!(
+        "| [Executions](/executions/) | {} | [{}]({}) |\n",
+        store
+            .get_executions()
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        store
+            .get_executions()
+            .update_time(datetime_to_duration(since)..)
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        period_url(ExecutionOrderByField::UpdateTime, "", since, None)
+    );
+    md += &format!(
+        "| [Contexts](/contexts/) | {} | [{}]({}) |\n",
+        store
+            .get_contexts()
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        store
+            .get_contexts()
+            .update_time(datetime_to_duration(since)..)
+            .count()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        period_url(ContextOrderByField::UpdateTime, "", since, None)
+    );
+
+    md += "\n## Recent Activity\n";
+    md += "\n| date | artifacts | executions | contexts |\n";
+    md += "|------|-----------|------------|----------|\n";
+    // Day `i` is `days[i]..days[i + 1]` (the oldest first).
+    let days = (0..=query.days())
+        .rev()
+        .map(|i| today - chrono::Duration::days(i - 1))
+        .collect::<Vec<_>>();
+    let boundaries = days
+        .iter()
+        .copied()
+        .map(datetime_to_duration)
+        .collect::<Vec<_>>();
+    let mut counts = Vec::new();
+    for item_type in [ItemType::Artifact, ItemType::Execution, ItemType::Context] {
+        counts.push(
+            stats::count_by_period(&mut conn, item_type, &boundaries)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+        );
+    }
+    for i in (0..days.len() - 1).rev() {
+        let (start, end) = (days[i], days[i + 1]);
+        md += &format!(
+            "| {} | [{}]({}) | [{}]({}) | [{}]({}) |\n",
+            tz.convert(start).format("%Y-%m-%d"),
+            counts[0][i],
+            period_url(ArtifactOrderByField::UpdateTime, "", start, Some(end)),
+            counts[1][i],
+            period_url(ExecutionOrderByField::UpdateTime, "", start, Some(end)),
+            counts[2][i],
+            period_url(ContextOrderByField::UpdateTime, "", start, Some(end)),
+        );
+    }
+
+    md += "\n## States\n";
+    let artifact_states =
+        stats::count_by_state(&mut conn, ItemType::Artifact, datetime_to_duration(since))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+    let execution_states =
+        stats::count_by_state(&mut conn, ItemType::Execution, datetime_to_duration(since))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    // The states are listed in the order of their values in ML Metadata.
+    md += "\n| artifact state | count |\n";
+    md += "|----------------|-------|\n";
+    for (value, state) in [
+        ArtifactState::Unknown,
+        ArtifactState::Pending,
+        ArtifactState::Live,
+        ArtifactState::MarkedForDeletion,
+        ArtifactState::Deleted,
+    ]
+    .iter()
+    .enumerate()
+    {
+        md += &format!(
+            "| {} | {} |\n",
+            state,
+            artifact_states.get(&(value as i32)).copied().unwrap_or(0)
+        );
+    }
+    md += "\n| execution state | count |\n";
+    md += "|-----------------|-------|\n";
+    for (value, state) in [
+        ExecutionState::Unknown,
+        ExecutionState::New,
+        ExecutionState::Running,
+        ExecutionState::Complete,
+        ExecutionState::Failed,
+        ExecutionState::Cached,
+        ExecutionState::Canceled,
+    ]
+    .iter()
+    .enumerate()
+    {
+        md += &format!(
+            "| {} | {} |\n",
+            state,
+            execution_states.get(&(value as i32)).copied().unwrap_or(0)
+        );
+    }
+
+    md += "\n## Artifact Types\n";
+    md += "\n| type | total | updated |\n";
+    md += "|------|-------|---------|\n";
+    let types = store
+        .get_artifact_types()
+        .execute()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let totals = stats::count_by_type(&mut conn, ItemType::Artifact, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let updated = stats::count_by_type(
+        &mut conn,
+        ItemType::Artifact,
+        Some(datetime_to_duration(since)),
+    )
+    .await
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+    for ty in types {
+        md += &format!(
+            "| [{}](/artifact_types/{}) | [{}]({}) | [{}]({}) |\n",
+            ty.name,
+            ty.id.get(),
+            totals.get(&ty.id.get()).copied().unwrap_or(0),
+            GetArtifactsQuery::default().filter_type(&ty.name).to_url(),
+            updated.get(&ty.id.get()).copied().unwrap_or(0),
+            period_url(ArtifactOrderByField::UpdateTime, &ty.name, since, None)
+        );
+    }
+
+    md += "\n## Execution Types\n";
+    md += "\n| type | total | updated |\n";
+    md += "|------|-------|---------|\n";
+    let types = store
+        .get_execution_types()
+        .execute()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let totals = stats::count_by_type(&mut conn, ItemType::Execution, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let updated = stats::count_by_type(
+        &mut conn,
+        ItemType::Execution,
+        Some(datetime_to_duration(since)),
+    )
+    .await
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+    for ty in types {
+        md += &format!(
+            "| [{}](/execution_types/{}) | [{}]({}) | [{}]({}) |\n",
+            ty.name,
+            ty.id.get(),
+            totals.get(&ty.id.get()).copied().unwrap_or(0),
+            GetExecutionsQuery::default().filter_type(&ty.name).to_url(),
+            updated.get(&ty.id.get()).copied().unwrap_or(0),
+            period_url(ExecutionOrderByField::UpdateTime, &ty.name, since, None)
+        );
+    }
+
+    md += "\n## Context Types\n";
+    md += "\n| type | total | updated |\n";
+    md += "|------|-------|---------|\n";
+    let types = store
+        .get_context_types()
+        .execute()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let totals = stats::count_by_type(&mut conn, ItemType::Context, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let updated = stats::count_by_type(
+        &mut conn,
+        ItemType::Context,
+        Some(datetime_to_duration(since)),
+    )
+    .await
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+    for ty in types {
+        md += &format!(
+            "| [{}](/context_types/{}) | [{}]({}) | [{}]({}) |\n",
+            ty.name,
+            ty.id.get(),
+            totals.get(&ty.id.get()).copied().unwrap_or(0),
+            GetContextsQuery::default().filter_type(&ty.name).to_url(),
+            updated.get(&ty.id.get()).copied().unwrap_or(0),
+            period_url(ContextOrderByField::UpdateTime, &ty.name, since, None)
+        );
+    }
+
+    md += "\n## Largest Contexts\n";
+    let sizes = stats::get_largest_contexts(&mut conn, LARGEST_CONTEXTS_LIMIT)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if sizes.is_empty() {
+        // An empty ID list would fetch all the contexts.
+        md += "\nNo contexts have executions or artifacts.\n";
+        return Ok(response::markdown(&md));
+    }
+    let contexts = store
+        .get_contexts()
+        .ids(
+            sizes
+                .iter()
+                .map(|x| mlmd::metadata::ContextId::new(x.context_id)),
+        )
+        .execute()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|c| (c.id.get(), c))
+        .collect::<HashMap<_, _>>();
+    let context_types = store
+        .get_context_types()
+        .ids(
+            contexts
+                .values()
+                .map(|c| c.type_id)
+                .collect::<HashSet<_>>()
+                .into_iter(),
+        )
+        .execute()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|t| (t.id, t.name))
+        .collect::<HashMap<_, _>>();
+
+    md += "\n| context | type | name | executions | artifacts |\n";
+    md += "|---------|------|------|------------|-----------|\n";
+    for size in sizes {
+        let c = if let Some(c) = contexts.get(&size.context_id) {
+            c
+        } else {
+            continue;
+        };
+        md += &format!(
+            "| [{}](/contexts/{}) | {} | {} | [{}](/executions/?context={}) | [{}](/artifacts/?context={}) |\n",
+            c.id.get(),
+            c.id.get(),
+            context_types.get(&c.type_id).map_or("", |x| x.as_str()),
+            c.name,
+            size.executions,
+            c.id.get(),
+            size.artifacts,
+            c.id.get()
+        );
+    }
+
+    Ok(response::markdown(&md))
+}
+
+fn period_url<F: OrderByField>(
+    order_by: F,
+    type_name: &str,
+    start: DateTime,
+    end: Option<DateTime>,
+) -> String {
+    ListQuery {
+        type_name: Some(type_name.to_owned()).filter(|x| !x.is_empty()),
+        order_by,
+        mtime_start: Some(start),
+        mtime_end: end,
+        ..Default::default()
+    }
+    .to_url()
 }
@@ -0,0 +1,18 @@
+use crate::web::{response, Config};
+use actix_web::{get, web, HttpResponse};
+
+#[get("/js/plotly.min.js")]
+async fn get_plotly_js(config: web::Data<Config>) -> actix_web::Result<HttpResponse> {
+    Ok(response::js(
+        config
+            .plotly_js()
+            .unwrap_or(include_str!("../../../js/plotly.min.js")),
+    ))
+}
+
+#[get("/js/lineage-explorer.js")]
+async fn get_lineage_explorer_js() -> actix_web::Result<HttpResponse> {
+    Ok(response::js(include_str!(
+        "../../../js/lineage-explorer.js"
+    )))
+}
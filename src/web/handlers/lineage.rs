@@ -0,0 +1,106 @@
+use crate::graph::{Graph, GraphFormat, GraphStyle, NodeId};
+use crate::web::{response, Config};
+use actix_web::{get, web, HttpResponse};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LineageQuery {
+    /// Node ID such as `A12` (artifact) or `E34` (execution).
+    pub node: NodeId,
+}
+
+#[get("/lineage/neighbors")]
+pub async fn get_lineage_neighbors(
+    config: web::Data<Config>,
+    query: web::Query<LineageQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
+    let graph = Graph::neighbors(&mut store, query.node)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let json = serde_json::to_string(&graph).map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(response::json(&json))
+}
+
+#[get("/lineage/explorer")]
+pub async fn get_lineage_explorer(
+    query: web::Query<LineageQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let md = format!(
+        r#"# Lineage Explorer
+
+Click a node to expand its neighbours, shift+click another node to highlight the path between them,
+and double-click a node to open its detail page. Drag to pan and scroll to zoom.
+
+<div id='lineage-explorer' data-node='{}'></div>
+
+<script src='/js/lineage-explorer.js'></script>
+"#,
+        query.node
+    );
+    Ok(response::markdown(&md))
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetGraphQuery {
+    pub format: Option<GraphFormat>,
+}
+
+/// Builds the lineage graph of `root` for the `/{artifacts,executions}/{id}/graph` endpoints.
+///
+/// The summaries of the nodes are filled by the hooks,
+/// and the nodes are grouped by context unless disabled by the graph style.
+pub async fn get_graph(config: &Config, root: NodeId) -> actix_web::Result<Graph> {
+    let mut store = config.connect_metadata_store().await?;
+    let mut graph = Graph::new(&mut store, root)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    config
+        .hook_runner
+        .run_graph_summary_hook(&mut graph)
+        .await?;
+    if config.graph_style().cluster_contexts {
+        let mut conn = config.connect_database().await?;
+        graph
+            .load_clusters(&mut conn, config.graph_style())
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+    }
+    Ok(graph)
+}
+
+/// Makes the response of the `/{artifacts,executions}/{id}/graph` endpoints.
+///
+/// Without an explicit format, the graph is rendered as SVG
+/// and falls back to a DOT listing if Graphviz isn't available.
+pub fn graph_response(
+    graph: &Graph,
+    style: &GraphStyle,
+    format: Option<GraphFormat>,
+) -> actix_web::Result<HttpResponse> {
+    if let Some(format) = format {
+        let body = graph
+            .to_bytes(format, style)
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        return Ok(response::bytes(format.content_type(), body));
+    }
+
+    if let Ok(svg) = graph.render_image("svg", style) {
+        return Ok(response::bytes(GraphFormat::Svg.content_type(), svg));
+    }
+    let dot = graph
+        .to_bytes(GraphFormat::Dot, style)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let dot = String::from_utf8(dot).map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(response::markdown(&format!("```dot\n{}```\n", dot)))
+}
+
+/// Makes the links to download the graph at `url` in each format.
+pub fn graph_formats_md(url: &str) -> String {
+    GraphFormat::ALL
+        .iter()
+        .map(|f| format!("[{}]({}?format={})", f, url, f))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,31 +1,427 @@
-use crate::mlmd::artifact::ArtifactOrderByField;
+use crate::graph::{BatchStore, Node, NodeId};
+use crate::hook::ItemType;
+use crate::mlmd::hierarchy;
+use crate::mlmd::property::PropertyValue;
+use crate::time::{duration_to_datetime, DateTime, TimeZone};
 use crate::web::handlers::artifacts::GetArtifactsQuery;
-use crate::web::{response, Config};
-use actix_web::{get, web, HttpResponse};
+use crate::web::handlers::contexts::GetContextsQuery;
+use crate::web::handlers::executions::GetExecutionsQuery;
+use crate::web::query;
+use crate::web::{response, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use plotly::common::Mode;
 use plotly::{Histogram, Plot, Scatter};
-use std::collections::{BTreeMap, BTreeSet};
-use std::io::Read;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Prefix to distinguish custom properties from properties in plot parameters.
+const CUSTOM_PROPERTY_PREFIX: &str = "custom:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ItemKind {
+    #[default]
+    Artifact,
+    Execution,
+    Context,
+}
+
+impl ItemKind {
+    const ALL: &'static [Self] = &[Self::Artifact, Self::Execution, Self::Context];
+}
+
+impl std::fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Artifact => write!(f, "Artifact"),
+            Self::Execution => write!(f, "Execution"),
+            Self::Context => write!(f, "Context"),
+        }
+    }
+}
+
+/// Items to be plotted.
+#[derive(Debug, Clone)]
+pub enum PlotTarget {
+    Artifacts(GetArtifactsQuery),
+    Executions(GetExecutionsQuery),
+    Contexts(GetContextsQuery),
+}
+
+impl PlotTarget {
+    /// Makes the target of `kind` from the list query parameters in the query string of `req`.
+    ///
+    /// This is extracted separately from the plot parameters
+    /// because `#[serde(flatten)]` can't deserialize non-string values from query strings.
+    fn from_request(kind: ItemKind, req: &HttpRequest) -> actix_web::Result<Self> {
+        let qs = req.query_string();
+        Ok(match kind {
+            ItemKind::Artifact => {
+                Self::Artifacts(web::Query::<GetArtifactsQuery>::from_query(qs)?.into_inner())
+            }
+            ItemKind::Execution => {
+                Self::Executions(web::Query::<GetExecutionsQuery>::from_query(qs)?.into_inner())
+            }
+            ItemKind::Context => {
+                Self::Contexts(web::Query::<GetContextsQuery>::from_query(qs)?.into_inner())
+            }
+        })
+    }
+
+    fn new(kind: ItemKind) -> Self {
+        match kind {
+            ItemKind::Artifact => Self::Artifacts(Default::default()),
+            ItemKind::Execution => Self::Executions(Default::default()),
+            ItemKind::Context => Self::Contexts(Default::default()),
+        }
+    }
+
+    fn kind(&self) -> ItemKind {
+        match self {
+            Self::Artifacts(_) => ItemKind::Artifact,
+            Self::Executions(_) => ItemKind::Execution,
+            Self::Contexts(_) => ItemKind::Context,
+        }
+    }
+
+    async fn get_items(
+        &self,
+        store: &mut mlmd::MetadataStore,
+        conn: &mut sqlx::AnyConnection,
+    ) -> anyhow::Result<Vec<PlotItem>> {
+        Ok(match self {
+            Self::Artifacts(query) => {
+                let mut query = query.clone();
+                query
+                    .resolve_context_filters(conn, ItemType::Artifact)
+                    .await?;
+                query
+                    .get_artifacts(store, conn)
+                    .await?
+                    .into_iter()
+                    .map(|x| PlotItem {
+                        id: x.id.get(),
+                        ctime: duration_to_datetime(x.create_time_since_epoch),
+                        mtime: duration_to_datetime(x.last_update_time_since_epoch),
+                        properties: plot_properties(x.properties, x.custom_properties),
+                    })
+                    .collect()
+            }
+            Self::Executions(query) => {
+                let mut query = query.clone();
+                query
+                    .resolve_context_filters(conn, ItemType::Execution)
+                    .await?;
+                query
+                    .get_executions(store, conn)
+                    .await?
+                    .into_iter()
+                    .map(|x| PlotItem {
+                        id: x.id.get(),
+                        ctime: duration_to_datetime(x.create_time_since_epoch),
+                        mtime: duration_to_datetime(x.last_update_time_since_epoch),
+                        properties: plot_properties(x.properties, x.custom_properties),
+                    })
+                    .collect()
+            }
+            Self::Contexts(query) => query
+                .get_contexts(store, conn)
+                .await?
+                .into_iter()
+                .map(|x| PlotItem {
+                    id: x.id.get(),
+                    ctime: duration_to_datetime(x.create_time_since_epoch),
+                    mtime: duration_to_datetime(x.last_update_time_since_epoch),
+                    properties: plot_properties(x.properties, x.custom_properties),
+                })
+                .collect(),
+        })
+    }
+
+    /// Gets the names of the contexts that each of `items` belongs to (empty if the target is contexts).
+    async fn get_item_contexts(
+        &self,
+        conn: &mut sqlx::AnyConnection,
+        items: &[PlotItem],
+    ) -> anyhow::Result<HashMap<i32, Vec<String>>> {
+        let ids = items.iter().map(|x| x.id).collect::<Vec<_>>();
+        let memberships = match self {
+            Self::Artifacts(_) => hierarchy::get_artifact_memberships(conn, &ids).await?,
+            Self::Executions(_) => hierarchy::get_execution_memberships(conn, &ids).await?,
+            Self::Contexts(_) => Vec::new(),
+        };
+        let mut contexts: HashMap<_, Vec<_>> = HashMap::new();
+        for x in memberships {
+            contexts.entry(x.item_id).or_default().push(x.context_name);
+        }
+        Ok(contexts)
+    }
+
+    /// Follows the events from `items` through the nodes whose type names are `path`.
+    ///
+    /// Artifacts and executions alternate on the path
+    /// (e.g., model artifact -> evaluation execution -> metrics artifact).
+    async fn get_lineage(
+        &self,
+        store: &mut mlmd::MetadataStore,
+        items: &[&PlotItem],
+        path: &[&str],
+    ) -> anyhow::Result<Lineage> {
+        let node_id = match self.kind() {
+            ItemKind::Artifact => NodeId::Artifact,
+            ItemKind::Execution => NodeId::Execution,
+            ItemKind::Context => {
+                anyhow::bail!("lineage joins are only available for artifacts and executions")
+            }
+        };
+
+        let mut store = BatchStore::new(store);
+        let mut lineage = Lineage::default();
+        let mut reached = Vec::new();
+        let mut frontier = items
+            .iter()
+            .map(|x| {
+                (
+                    node_id(x.id),
+                    std::iter::once(x.id).collect::<BTreeSet<_>>(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        for step in 0..=path.len() {
+            let ids = frontier.keys().copied().collect::<Vec<_>>();
+            let mut neighbors: HashMap<NodeId, BTreeSet<i32>> = HashMap::new();
+            for (curr, event) in store.get_events(&ids).await? {
+                let neighbor = match curr {
+                    NodeId::Artifact(_) => NodeId::Execution(event.execution_id.get()),
+                    NodeId::Execution(_) => NodeId::Artifact(event.artifact_id.get()),
+                };
+                neighbors
+                    .entry(neighbor)
+                    .or_default()
+                    .extend(frontier[&curr].iter().copied());
+            }
+
+            let ids = neighbors.keys().copied().collect::<Vec<_>>();
+            let nodes = store
+                .get_nodes(&ids)
+                .await?
+                .into_iter()
+                .map(|node| (node.id(), node))
+                .collect::<HashMap<_, _>>();
+            if let Some(type_name) = path.get(step) {
+                frontier = neighbors
+                    .into_iter()
+                    .filter(|(id, _)| nodes.get(id).map(|x| x.type_name()) == Some(*type_name))
+                    .collect();
+                reached = nodes
+                    .into_iter()
+                    .filter(|(id, _)| frontier.contains_key(id))
+                    .map(|x| x.1)
+                    .collect();
+            } else {
+                lineage.next_types = nodes.values().map(|x| x.type_name().to_owned()).collect();
+            }
+        }
+
+        for (id, origins) in frontier {
+            let id = match id {
+                NodeId::Artifact(id) | NodeId::Execution(id) => id,
+            };
+            for origin in origins {
+                lineage.reached.entry(origin).or_default().insert(id);
+            }
+        }
+        lineage.items = reached.into_iter().map(PlotItem::from).collect();
+        Ok(lineage)
+    }
+
+    fn to_url(&self) -> String {
+        match self {
+            Self::Artifacts(query) => query.to_url(),
+            Self::Executions(query) => query.to_url(),
+            Self::Contexts(query) => query.to_url(),
+        }
+    }
+
+    fn to_qs(&self) -> String {
+        match self {
+            Self::Artifacts(query) => query.to_qs(),
+            Self::Executions(query) => query.to_qs(),
+            Self::Contexts(query) => query.to_qs(),
+        }
+    }
+}
+
+impl Default for PlotTarget {
+    fn default() -> Self {
+        Self::new(ItemKind::default())
+    }
+}
+
+/// Items reached from the target items by following events.
+#[derive(Debug, Default)]
+struct Lineage {
+    /// Map from the ID of a target item to the IDs of the items reached from it.
+    reached: HashMap<i32, BTreeSet<i32>>,
+
+    /// Items at the end of the path (i.e., the y-axis items).
+    items: Vec<PlotItem>,
+
+    /// Type names of the nodes next to the reached items (i.e., candidates of the next step).
+    next_types: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PlotItem {
+    id: i32,
+    ctime: DateTime,
+    mtime: DateTime,
+
+    /// Properties and custom properties (keyed by `CUSTOM_PROPERTY_PREFIX` + name).
+    properties: Vec<(String, mlmd::metadata::PropertyValue)>,
+}
+
+impl PlotItem {
+    fn get(&self, key: &str) -> Option<&mlmd::metadata::PropertyValue> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+}
+
+impl From<Node> for PlotItem {
+    fn from(node: Node) -> Self {
+        let (id, ctime, mtime, properties, custom_properties) = match node {
+            Node::Artifact { node, .. } => (
+                node.id,
+                node.ctime,
+                node.mtime,
+                node.properties,
+                node.custom_properties,
+            ),
+            Node::Execution { node, .. } => (
+                node.id,
+                node.ctime,
+                node.mtime,
+                node.properties,
+                node.custom_properties,
+            ),
+        };
+        Self {
+            id,
+            ctime,
+            mtime,
+            properties: plot_properties(
+                properties
+                    .into_iter()
+                    .map(|(k, v)| (k, raw_property_value(v))),
+                custom_properties
+                    .into_iter()
+                    .map(|(k, v)| (k, raw_property_value(v))),
+            ),
+        }
+    }
+}
+
+/// Strips the links added by hooks from `value`.
+fn raw_property_value(value: PropertyValue) -> mlmd::metadata::PropertyValue {
+    match value {
+        PropertyValue::Int(x) => mlmd::metadata::PropertyValue::Int(x),
+        PropertyValue::Double(x) => mlmd::metadata::PropertyValue::Double(x),
+        PropertyValue::String(x) => mlmd::metadata::PropertyValue::String(x),
+        PropertyValue::Link { value, .. } => raw_property_value(*value),
+    }
+}
+
+fn plot_properties<P, C>(
+    properties: P,
+    custom_properties: C,
+) -> Vec<(String, mlmd::metadata::PropertyValue)>
+where
+    P: IntoIterator<Item = (String, mlmd::metadata::PropertyValue)>,
+    C: IntoIterator<Item = (String, mlmd::metadata::PropertyValue)>,
+{
+    properties
+        .into_iter()
+        .chain(
+            custom_properties
+                .into_iter()
+                .map(|(k, v)| (format!("{}{}", CUSTOM_PROPERTY_PREFIX, k), v)),
+        )
+        .collect()
+}
+
+/// Returns the value of the string property `key` of `item` used to split the items into series.
+fn series_name<'a>(key: &Option<String>, item: &'a PlotItem) -> &'a str {
+    key.as_ref()
+        .and_then(|k| item.get(k))
+        .and_then(get_string)
+        .unwrap_or("")
+}
+
+/// Makes the markdown of the links to plot the items of `target`.
+pub fn plot_links_md(target: PlotTarget) -> String {
+    format!(
+        "Plot: [Histogram]({}) [Scatter]({}) [Line]({}) [Distribution]({}) [Parallel]({})",
+        PlotHistogramQuery {
+            item: target.kind(),
+            target: target.clone(),
+            ..Default::default()
+        }
+        .to_url(),
+        PlotScatterQuery {
+            item: target.kind(),
+            target: target.clone(),
+            ..Default::default()
+        }
+        .to_url(),
+        PlotLineQuery {
+            item: target.kind(),
+            target: target.clone(),
+            ..Default::default()
+        }
+        .to_url(),
+        PlotDistributionQuery {
+            item: target.kind(),
+            target: target.clone(),
+            ..Default::default()
+        }
+        .to_url(),
+        PlotParallelQuery {
+            item: target.kind(),
+            target,
+            ..Default::default()
+        }
+        .to_url()
+    )
+}
+
+/// Makes the markdown of the links to switch the kind of the target items.
+fn item_kind_links_md<F>(current: ItemKind, url: F) -> String
+where
+    F: Fn(ItemKind) -> String,
+{
+    let links = ItemKind::ALL
+        .iter()
+        .map(|&kind| {
+            if kind == current {
+                format!("**{}s**", kind)
+            } else {
+                format!("[{}s]({})", kind, url(kind))
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("- Target: {}\n", links.join(" | "))
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct PlotHistogramQuery {
-    // #[serde(flatten)]
-    // artifacts: GetArtifactsQuery,
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
     #[serde(default)]
-    pub order_by: ArtifactOrderByField,
-    #[serde(default)]
-    pub asc: bool,
+    pub item: ItemKind,
+
+    #[serde(skip)]
+    pub target: PlotTarget,
 
     #[serde(default)]
     pub metric: Option<String>,
@@ -36,25 +432,14 @@ pub struct PlotHistogramQuery {
     #[serde(default)]
     pub group_value: Option<String>,
 
+    #[serde(default)]
+    pub series_key: Option<String>,
+
     #[serde(default)]
     pub do_plot: bool,
 }
 
 impl PlotHistogramQuery {
-    fn artifacts(&self) -> GetArtifactsQuery {
-        GetArtifactsQuery {
-            type_name: self.type_name.clone(),
-            name: self.name.clone(),
-            context: self.context.clone(),
-            limit: self.limit.clone(),
-            offset: self.offset.clone(),
-            order_by: self.order_by.clone(),
-            asc: self.asc.clone(),
-            mtime_start: None,
-            mtime_end: None,
-        }
-    }
-
     fn metric(&self, name: &str) -> Self {
         let mut this = self.clone();
         this.metric = Some(name.to_owned());
@@ -68,6 +453,20 @@ impl PlotHistogramQuery {
         this
     }
 
+    fn series_key(&self, key: &str) -> Self {
+        let mut this = self.clone();
+        this.series_key = Some(key.to_owned());
+        this
+    }
+
+    fn item(&self, kind: ItemKind) -> Self {
+        Self {
+            item: kind,
+            target: PlotTarget::new(kind),
+            ..Default::default()
+        }
+    }
+
     fn do_plot(&self) -> Self {
         let mut this = self.clone();
         this.do_plot = true;
@@ -75,20 +474,10 @@ impl PlotHistogramQuery {
     }
 
     fn to_url(&self) -> String {
-        let mut qs = self.artifacts().to_qs();
-        if let Some(x) = &self.metric {
-            qs += &format!("&metric={}", x);
-        }
-        if let Some(x) = &self.group_key {
-            qs += &format!("&group-key={}", x);
-        }
-        if let Some(x) = &self.group_value {
-            qs += &format!("&group-value={}", x);
-        }
-        if self.do_plot {
-            qs += &format!("&do-plot=true");
-        }
-        format!("/plot/histogram?{}", qs)
+        format!(
+            "/plot/histogram?{}",
+            query::join_qs(&[&self.target.to_qs(), &query::to_qs(self)])
+        )
     }
 }
 
@@ -96,99 +485,100 @@ impl PlotHistogramQuery {
 pub async fn plot_histogram(
     config: web::Data<Config>,
     query: web::Query<PlotHistogramQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let query = PlotHistogramQuery {
+        target: PlotTarget::from_request(query.item, &req)?,
+        ..query.into_inner()
+    };
     let mut store = config.connect_metadata_store().await?;
-    let artifacts = query
-        .artifacts()
-        .get_artifacts(&mut store)
+    let mut conn = config.connect_database().await?;
+    let items = query
+        .target
+        .get_items(&mut store, &mut conn)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut md = format!(
-        "# Histogram Plot ([target artifacts]({}))\n",
-        query.artifacts().to_url()
+        "# Histogram Plot ([target {}s]({}))\n",
+        query.item.to_string().to_lowercase(),
+        query.target.to_url()
     );
+    md += &item_kind_links_md(query.item, |kind| query.item(kind).to_url());
 
     let metric_property = if let Some(metric) = &query.metric {
-        md += &format!("- Metric property: {}\n", metric);
+        md += &format!("- Metric property: {}\n", property_label(metric));
         metric
     } else {
         md += "Select metric value property:\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if is_int_or_double(v) {
-                    candidates.insert(k);
-                }
-            }
-        }
-        for c in candidates {
-            md += &format!("- [{}]({})\n", c, query.metric(c).to_url());
+        for c in candidates(&items, is_int_or_double) {
+            md += &format!("- [{}]({})\n", property_label(c), query.metric(c).to_url());
         }
         return Ok(response::markdown(&md));
     };
 
-    if query.group_key.is_none() {
+    if let (Some(key), Some(value)) = (&query.group_key, &query.group_value) {
+        md += &format!("- Target property: {}={}\n", property_label(key), value);
+    } else {
         md += "\nSelect target property (optional):\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if let (k, Some(v)) = (k, get_string(v)) {
-                    candidates.insert((k, v));
-                }
-            }
-        }
-        for (k, v) in candidates {
-            md += &format!("- [{}] [{}]({})\n", k, v, query.group(k, v).to_url());
+        for (k, v) in value_candidates(&items) {
+            md += &format!(
+                "- [{}] [{}]({})\n",
+                property_label(k),
+                v,
+                query.group(k, v).to_url()
+            );
         }
+    }
+
+    if let Some(key) = &query.series_key {
+        md += &format!("- Series property: {}\n", property_label(key));
     } else {
-        md += &format!(
-            "- Target property: {}={}\n",
-            query.group_key.as_ref().unwrap(),
-            query.group_value.as_ref().unwrap()
-        );
+        md += "\nSelect series property (optional):\n";
+        for c in candidates(&items, |v| get_string(v).is_some()) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.series_key(c).to_url()
+            );
+        }
     }
 
-    let mut metrics = Vec::new();
-    for a in &artifacts {
+    let mut series: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for a in &items {
         if let (Some(tk), Some(tv)) = (query.group_key.as_ref(), query.group_value.as_ref()) {
-            let mut is_target = false;
-            for (k, v) in &a.properties {
-                if k == tk && get_string(v) == Some(tv) {
-                    is_target = true;
-                }
-            }
-            if !is_target {
+            if a.get(tk).and_then(get_string) != Some(tv) {
                 continue;
             }
         }
 
-        for (k, v) in &a.properties {
-            if k != metric_property {
-                continue;
-            }
-            if let Some(m) = get_double(v) {
-                metrics.push(m);
-                break;
-            }
+        if let Some(m) = a.get(metric_property).and_then(get_double) {
+            series
+                .entry(series_name(&query.series_key, a))
+                .or_default()
+                .push(m);
         }
     }
 
     if !query.do_plot {
-        md += &format!("- n_target: {}\n", metrics.len());
+        md += &format!(
+            "- n_target: {}\n",
+            series.values().map(|x| x.len()).sum::<usize>()
+        );
+        md += &format!("- n_series: {}\n", series.len());
         md += &format!("\n**[Do plot]({})**\n", query.do_plot().to_url());
         return Ok(response::markdown(&md));
     }
 
     let mut plot = Plot::new();
-    let trace = Histogram::new(metrics);
-    plot.add_trace(trace);
+    for (name, metrics) in series {
+        let trace = Histogram::new(metrics).name(&trace_name(name, metric_property, false));
+        plot.add_trace(trace);
+    }
 
-    let mut html = String::new();
-    let mut html_file = tempfile::NamedTempFile::new()?;
-    plot.to_html(html_file.path());
-    html_file.read_to_string(&mut html)?;
-    Ok(response::html(&html))
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "plot");
+    Ok(response::markdown(&md))
 }
 
 fn is_int_or_double(p: &mlmd::metadata::PropertyValue) -> bool {
@@ -224,25 +614,119 @@ fn get_double(p: &mlmd::metadata::PropertyValue) -> Option<f64> {
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Returns the keys of the properties whose values satisfy `f`.
+fn candidates<F>(items: &[PlotItem], f: F) -> BTreeSet<&str>
+where
+    F: Fn(&mlmd::metadata::PropertyValue) -> bool,
+{
+    items
+        .iter()
+        .flat_map(|a| a.properties.iter())
+        .filter(|(_, v)| f(v))
+        .map(|(k, _)| k.as_str())
+        .collect()
+}
+
+/// Returns the key-value pairs of the string properties.
+fn value_candidates(items: &[PlotItem]) -> BTreeSet<(&str, &str)> {
+    items
+        .iter()
+        .flat_map(|a| a.properties.iter())
+        .filter_map(|(k, v)| get_string(v).map(|v| (k.as_str(), v)))
+        .collect()
+}
+
+/// Returns the human-readable label of a property key.
+fn property_label(key: &str) -> String {
+    if let Some(name) = key.strip_prefix(CUSTOM_PROPERTY_PREFIX) {
+        format!("{} (custom)", name)
+    } else {
+        key.to_owned()
+    }
+}
+
+fn trace_name(series: &str, metric: &str, multiple_metrics: bool) -> String {
+    match (series.is_empty(), multiple_metrics) {
+        (true, _) => property_label(metric),
+        (false, false) => series.to_owned(),
+        (false, true) => format!("{} {}", series, property_label(metric)),
+    }
+}
+
+/// Splits a comma-separated list of metric properties.
+fn split_metrics(metrics: &Option<String>) -> Vec<&str> {
+    metrics
+        .as_ref()
+        .map(|x| x.split(',').filter(|x| !x.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn add_metric(metrics: &Option<String>, name: &str) -> Option<String> {
+    let mut metrics = split_metrics(metrics);
+    metrics.push(name);
+    Some(metrics.join(","))
+}
+
+fn remove_metric(metrics: &Option<String>, name: &str) -> Option<String> {
+    let metrics = split_metrics(metrics)
+        .into_iter()
+        .filter(|x| *x != name)
+        .collect::<Vec<_>>();
+    Some(metrics.join(",")).filter(|x| !x.is_empty())
+}
+
+/// Makes the markdown to select multiple metric properties.
+fn metrics_selector_md<F, G>(
+    label: &str,
+    items: &[PlotItem],
+    selected: &[&str],
+    add_url: F,
+    remove_url: G,
+) -> String
+where
+    F: Fn(&str) -> String,
+    G: Fn(&str) -> String,
+{
+    let mut md = String::new();
+    if !selected.is_empty() {
+        md += &format!(
+            "- **{} metric properties**: {}\n",
+            label,
+            selected
+                .iter()
+                .map(|m| format!("{} [x]({})", property_label(m), remove_url(m)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let candidates = candidates(items, is_int_or_double)
+        .into_iter()
+        .filter(|c| !selected.contains(c))
+        .collect::<Vec<_>>();
+    if !candidates.is_empty() {
+        if selected.is_empty() {
+            md += &format!("\nSelect {} metric property:\n", label.to_lowercase());
+        } else {
+            md += &format!(
+                "\nAdd {} metric property (optional):\n",
+                label.to_lowercase()
+            );
+        }
+        for c in candidates {
+            md += &format!("- [{}]({})\n", property_label(c), add_url(c));
+        }
+    }
+    md
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct PlotScatterQuery {
-    // #[serde(flatten)]
-    // artifacts: GetArtifactsQuery,
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub limit: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub offset: Option<usize>,
     #[serde(default)]
-    pub order_by: ArtifactOrderByField,
-    #[serde(default)]
-    pub asc: bool,
+    pub item: ItemKind,
+
+    #[serde(skip)]
+    pub target: PlotTarget,
 
     #[serde(default)]
     pub x_filter_key: Option<String>,
@@ -259,46 +743,74 @@ pub struct PlotScatterQuery {
     #[serde(default)]
     pub y_filter_value: Option<String>,
 
+    /// Comma-separated names of the y-axis metric properties.
     #[serde(default)]
-    pub y_metric: Option<String>,
+    pub y_metrics: Option<String>,
 
     #[serde(default)]
     pub join_key: Option<String>,
 
+    /// Comma-separated type names of the nodes on the lineage from an x-axis item to a y-axis item.
+    ///
+    /// An empty string means that the lineage join is enabled but no step is selected yet.
+    #[serde(default)]
+    pub join_lineage: Option<String>,
+
+    #[serde(default)]
+    pub series_key: Option<String>,
+
     #[serde(default)]
     pub do_plot: bool,
 }
 
 impl PlotScatterQuery {
-    fn artifacts(&self) -> GetArtifactsQuery {
-        GetArtifactsQuery {
-            type_name: self.type_name.clone(),
-            name: self.name.clone(),
-            context: self.context.clone(),
-            limit: self.limit.clone(),
-            offset: self.offset.clone(),
-            order_by: self.order_by.clone(),
-            asc: self.asc.clone(),
-            mtime_start: None,
-            mtime_end: None,
-        }
-    }
-
     fn x_metric(&self, name: &str) -> Self {
         let mut this = self.clone();
         this.x_metric = Some(name.to_owned());
         this
     }
 
-    fn y_metric(&self, name: &str) -> Self {
+    fn add_y_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.y_metrics = add_metric(&self.y_metrics, name);
+        this
+    }
+
+    fn remove_y_metric(&self, name: &str) -> Self {
         let mut this = self.clone();
-        this.y_metric = Some(name.to_owned());
+        this.y_metrics = remove_metric(&self.y_metrics, name);
+        this.do_plot = false;
         this
     }
 
     fn join_key(&self, name: &str) -> Self {
         let mut this = self.clone();
         this.join_key = Some(name.to_owned());
+        this.join_lineage = None;
+        this
+    }
+
+    fn join_lineage(&self, path: Option<String>) -> Self {
+        let mut this = self.clone();
+        this.join_key = None;
+        this.join_lineage = path;
+        this.do_plot = false;
+        this
+    }
+
+    fn add_lineage_step(&self, type_name: &str) -> Self {
+        self.join_lineage(add_metric(&self.join_lineage, type_name))
+    }
+
+    fn remove_lineage_step(&self) -> Self {
+        let mut steps = split_metrics(&self.join_lineage);
+        steps.pop();
+        self.join_lineage(Some(steps.join(",")))
+    }
+
+    fn series_key(&self, key: &str) -> Self {
+        let mut this = self.clone();
+        this.series_key = Some(key.to_owned());
         this
     }
 
@@ -316,6 +828,14 @@ impl PlotScatterQuery {
         this
     }
 
+    fn item(&self, kind: ItemKind) -> Self {
+        Self {
+            item: kind,
+            target: PlotTarget::new(kind),
+            ..Default::default()
+        }
+    }
+
     fn do_plot(&self) -> Self {
         let mut this = self.clone();
         this.do_plot = true;
@@ -323,32 +843,45 @@ impl PlotScatterQuery {
     }
 
     fn to_url(&self) -> String {
-        let mut qs = self.artifacts().to_qs();
-        if let Some(x) = &self.x_metric {
-            qs += &format!("&x-metric={}", x);
-        }
-        if let Some(x) = &self.y_metric {
-            qs += &format!("&y-metric={}", x);
-        }
-        if let Some(x) = &self.join_key {
-            qs += &format!("&join-key={}", x);
-        }
-        if let Some(x) = &self.x_filter_key {
-            qs += &format!("&x-filter-key={}", x);
-        }
-        if let Some(x) = &self.y_filter_key {
-            qs += &format!("&y-filter-key={}", x);
-        }
-        if let Some(x) = &self.x_filter_value {
-            qs += &format!("&x-filter-value={}", x);
-        }
-        if let Some(x) = &self.y_filter_value {
-            qs += &format!("&y-filter-value={}", x);
+        format!(
+            "/plot/scatter?{}",
+            query::join_qs(&[&self.target.to_qs(), &query::to_qs(self)])
+        )
+    }
+
+    /// Returns the ID used to join the x-axis and y-axis items.
+    fn join_id(&self, item: &PlotItem) -> i32 {
+        self.join_key
+            .as_ref()
+            .and_then(|k| item.get(k))
+            .and_then(get_int)
+            .unwrap_or(item.id)
+    }
+
+    /// Returns the IDs of the y-axis items to be joined with the x-axis `item`.
+    fn x_join_ids(&self, item: &PlotItem, lineage: Option<&Lineage>) -> Vec<i32> {
+        if let Some(lineage) = lineage {
+            lineage
+                .reached
+                .get(&item.id)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default()
+        } else {
+            vec![self.join_id(item)]
         }
-        if self.do_plot {
-            qs += &format!("&do-plot=true");
+    }
+
+    fn is_x_item(&self, item: &PlotItem) -> bool {
+        if let (Some(k), Some(v)) = (self.x_filter_key.as_ref(), self.x_filter_value.as_ref()) {
+            if item.get(k).and_then(get_string) != Some(v) {
+                return false;
+            }
         }
-        format!("/plot/scatter?{}", qs)
+        self.x_metric
+            .as_ref()
+            .and_then(|m| item.get(m))
+            .and_then(get_double)
+            .is_some()
     }
 }
 
@@ -356,220 +889,1029 @@ impl PlotScatterQuery {
 pub async fn plot_scatter(
     config: web::Data<Config>,
     query: web::Query<PlotScatterQuery>,
+    req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+    let query = PlotScatterQuery {
+        target: PlotTarget::from_request(query.item, &req)?,
+        ..query.into_inner()
+    };
     let mut store = config.connect_metadata_store().await?;
-    let artifacts = query
-        .artifacts()
-        .get_artifacts(&mut store)
+    let mut conn = config.connect_database().await?;
+    let items = query
+        .target
+        .get_items(&mut store, &mut conn)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut md = format!(
-        "# Scatter Plot ([target artifacts]({}))\n",
-        query.artifacts().to_url()
+        "# Scatter Plot ([target {}s]({}))\n",
+        query.item.to_string().to_lowercase(),
+        query.target.to_url()
     );
+    md += &item_kind_links_md(query.item, |kind| query.item(kind).to_url());
 
     let x_metric_property = if let Some(metric) = &query.x_metric {
-        md += &format!("- **X-axis metric property**: {}\n", metric);
+        md += &format!("- **X-axis metric property**: {}\n", property_label(metric));
         metric
     } else {
         md += "Select x-axis metric property:\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if is_int_or_double(v) {
-                    candidates.insert(k);
-                }
-            }
-        }
-        for c in candidates {
-            md += &format!("- [{}]({})\n", c, query.x_metric(c).to_url());
+        for c in candidates(&items, is_int_or_double) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.x_metric(c).to_url()
+            );
         }
         return Ok(response::markdown(&md));
     };
 
-    let y_metric_property = if let Some(metric) = &query.y_metric {
-        md += &format!("- **Y-axis metric property**: {}\n", metric);
-        metric
-    } else {
-        md += "\nSelect y-axis metric property:\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if is_int_or_double(v) {
-                    candidates.insert(k);
-                }
+    let mut lineage = None;
+    if let Some(key) = &query.join_key {
+        md += &format!("- **Join key property**: {}\n", property_label(key));
+    } else if query.join_lineage.is_some() {
+        let steps = split_metrics(&query.join_lineage);
+        md += &format!(
+            "- **Join lineage**: x-axis {} -> {}y-axis {} {}([cancel]({}))\n",
+            query.item.to_string().to_lowercase(),
+            steps
+                .iter()
+                .map(|s| format!("{} -> ", s))
+                .collect::<String>(),
+            query.item.to_string().to_lowercase(),
+            if steps.is_empty() {
+                String::new()
+            } else {
+                format!("[x]({}) ", query.remove_lineage_step().to_url())
+            },
+            query.join_lineage(None).to_url()
+        );
+
+        let x_items = items
+            .iter()
+            .filter(|a| query.is_x_item(a))
+            .collect::<Vec<_>>();
+        let l = query
+            .target
+            .get_lineage(&mut store, &x_items, &steps)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        if !l.next_types.is_empty() {
+            md += "\nAdd lineage step (optional):\n";
+            for t in &l.next_types {
+                md += &format!("- [{}]({})\n", t, query.add_lineage_step(t).to_url());
             }
         }
-        for c in candidates {
-            md += &format!("- [{}]({})\n", c, query.y_metric(c).to_url());
-        }
-        return Ok(response::markdown(&md));
-    };
 
-    if let Some(key) = &query.join_key {
-        md += &format!("- **Join key property**: {}\n", key);
-    } else {
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if is_int(v) {
-                    candidates.insert(k);
-                }
-            }
+        // Artifacts and executions alternate, so only paths of even length reach the target kind.
+        if steps.is_empty() || steps.len() % 2 == 1 {
+            md += &format!(
+                "\nThe lineage must end with the type of the y-axis {}s.\n",
+                query.item.to_string().to_lowercase()
+            );
+            return Ok(response::markdown(&md));
         }
+        lineage = Some(l);
+    } else {
+        let candidates = candidates(&items, is_int);
         if !candidates.is_empty() {
             md += "\nSelect join key property (optional):\n";
             for c in candidates {
-                md += &format!("- [{}]({})\n", c, query.join_key(c).to_url());
+                md += &format!(
+                    "- [{}]({})\n",
+                    property_label(c),
+                    query.join_key(c).to_url()
+                );
             }
         }
+        if query.item != ItemKind::Context {
+            md += &format!(
+                "\nOr [join via lineage]({}) (e.g., model -> evaluation -> metrics).\n",
+                query.join_lineage(Some(String::new())).to_url()
+            );
+        }
     };
 
-    if query.x_filter_key.is_none() {
-        md += "\nSelect x-axis filter property (optional):\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if let (k, Some(v)) = (k, get_string(v)) {
-                    candidates.insert((k, v));
-                }
-            }
-        }
-        for (k, v) in candidates {
-            md += &format!("- [{}] [{}]({})\n", k, v, query.x_filter(k, v).to_url());
-        }
-    } else {
+    // With a lineage join, the y-axis items are the ones reached from the x-axis items.
+    let y_items = lineage.as_ref().map_or(&items[..], |l| &l.items[..]);
+    let y_metrics = split_metrics(&query.y_metrics);
+    md += &metrics_selector_md(
+        "Y-axis",
+        y_items,
+        &y_metrics,
+        |m| query.add_y_metric(m).to_url(),
+        |m| query.remove_y_metric(m).to_url(),
+    );
+    if y_metrics.is_empty() {
+        return Ok(response::markdown(&md));
+    }
+
+    if let (Some(key), Some(value)) = (&query.x_filter_key, &query.x_filter_value) {
         md += &format!(
             "- **X-axis filter property**: {}={}\n",
-            query.x_filter_key.as_ref().unwrap(),
-            query.x_filter_value.as_ref().unwrap()
+            property_label(key),
+            value
         );
+    } else {
+        md += "\nSelect x-axis filter property (optional):\n";
+        for (k, v) in value_candidates(&items) {
+            md += &format!(
+                "- [{}] [{}]({})\n",
+                property_label(k),
+                v,
+                query.x_filter(k, v).to_url()
+            );
+        }
     }
 
-    if query.y_filter_key.is_none() {
-        md += "\nSelect y-axis filter property (optional):\n";
-        let mut candidates = BTreeSet::new();
-        for a in &artifacts {
-            for (k, v) in &a.properties {
-                if let (k, Some(v)) = (k, get_string(v)) {
-                    candidates.insert((k, v));
-                }
-            }
-        }
-        for (k, v) in candidates {
-            md += &format!("- [{}] [{}]({})\n", k, v, query.y_filter(k, v).to_url());
-        }
-    } else {
+    if let (Some(key), Some(value)) = (&query.y_filter_key, &query.y_filter_value) {
         md += &format!(
             "- **Y-axis filter property**: {}={}\n",
-            query.y_filter_key.as_ref().unwrap(),
-            query.y_filter_value.as_ref().unwrap()
+            property_label(key),
+            value
         );
+    } else {
+        md += "\nSelect y-axis filter property (optional):\n";
+        for (k, v) in value_candidates(y_items) {
+            md += &format!(
+                "- [{}] [{}]({})\n",
+                property_label(k),
+                v,
+                query.y_filter(k, v).to_url()
+            );
+        }
     }
 
-    let mut xs = BTreeMap::new();
-    for a in &artifacts {
-        if let (Some(tk), Some(tv)) = (query.x_filter_key.as_ref(), query.x_filter_value.as_ref()) {
-            let mut is_target = false;
-            for (k, v) in &a.properties {
-                if k == tk && get_string(v) == Some(tv) {
-                    is_target = true;
-                }
-            }
-            if !is_target {
-                continue;
-            }
+    if let Some(key) = &query.series_key {
+        md += &format!("- **Series property**: {}\n", property_label(key));
+    } else {
+        md += "\nSelect series property (optional):\n";
+        for c in candidates(y_items, |v| get_string(v).is_some()) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.series_key(c).to_url()
+            );
         }
+    }
 
-        let mut artifact_id = a.id.get();
-        if let Some(key) = &query.join_key {
-            for (k, v) in &a.properties {
-                if k != key {
-                    continue;
-                }
-                if let Some(n) = get_int(v) {
-                    artifact_id = n;
-                    break;
-                }
+    // Several x-axis items may be joined with the same y-axis item, so keep all of them.
+    let mut xs: BTreeMap<i32, Vec<(i32, f64)>> = BTreeMap::new();
+    for a in &items {
+        if !query.is_x_item(a) {
+            continue;
+        }
+        if let Some(m) = a.get(x_metric_property).and_then(get_double) {
+            for id in query.x_join_ids(a, lineage.as_ref()) {
+                xs.entry(id).or_default().push((a.id, m));
             }
-            // continue; // TODO: error check
-        };
+        }
+    }
 
-        for (k, v) in &a.properties {
-            if k != x_metric_property {
+    let mut ys: BTreeMap<(&str, &str), Vec<(i32, f64)>> = BTreeMap::new();
+    for a in y_items {
+        if let (Some(tk), Some(tv)) = (query.y_filter_key.as_ref(), query.y_filter_value.as_ref()) {
+            if a.get(tk).and_then(get_string) != Some(tv) {
                 continue;
             }
-            if let Some(m) = get_double(v) {
-                xs.insert(artifact_id, m);
-                break;
+        }
+        for metric in &y_metrics {
+            if let Some(m) = a.get(metric).and_then(get_double) {
+                ys.entry((series_name(&query.series_key, a), *metric))
+                    .or_default()
+                    .push((a.id, m));
             }
         }
     }
 
-    let mut ys = BTreeMap::new();
-    for a in &artifacts {
-        if let (Some(tk), Some(tv)) = (query.y_filter_key.as_ref(), query.y_filter_value.as_ref()) {
-            let mut is_target = false;
-            for (k, v) in &a.properties {
-                if k == tk && get_string(v) == Some(tv) {
-                    is_target = true;
+    let join_ids = y_items
+        .iter()
+        .map(|a| (a.id, query.join_id(a)))
+        .collect::<HashMap<_, _>>();
+    let mut traces = Vec::new();
+    for ((name, metric), ys) in ys {
+        let mut xs1 = Vec::new();
+        let mut ys1 = Vec::new();
+        let mut texts = Vec::new();
+        for (y_id, y) in ys {
+            // One point per (x, y) pair.
+            for &(x_id, x) in xs.get(&join_ids[&y_id]).into_iter().flatten() {
+                xs1.push(x);
+                ys1.push(y);
+                if x_id == y_id {
+                    texts.push(format!("{}: {}", query.item, y_id));
+                } else {
+                    texts.push(format!("{}: {} -> {}", query.item, x_id, y_id));
                 }
             }
-            if !is_target {
-                continue;
-            }
         }
+        traces.push((
+            trace_name(name, metric, y_metrics.len() > 1),
+            xs1,
+            ys1,
+            texts,
+        ));
+    }
 
-        let mut artifact_id = a.id.get();
-        if let Some(key) = &query.join_key {
-            for (k, v) in &a.properties {
-                if k != key {
-                    continue;
-                }
-                if let Some(n) = get_int(v) {
-                    artifact_id = n;
-                    break;
+    if !query.do_plot {
+        md += &format!(
+            "- n_target: {}\n",
+            traces.iter().map(|x| x.1.len()).sum::<usize>()
+        );
+        md += &format!("\n**[Do plot]({})**\n", query.do_plot().to_url());
+        return Ok(response::markdown(&md));
+    }
+
+    let mut plot = Plot::new();
+    for (name, xs, ys, texts) in traces {
+        let trace = Scatter::new(xs, ys)
+            .name(&name)
+            .mode(Mode::Markers)
+            .text_array(texts);
+        plot.add_trace(trace);
+    }
+
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "plot");
+    Ok(response::markdown(&md))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeField {
+    Ctime,
+    Mtime,
+}
+
+impl std::fmt::Display for TimeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Ctime => write!(f, "ctime"),
+            Self::Mtime => write!(f, "mtime"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PlotLineQuery {
+    #[serde(default)]
+    pub item: ItemKind,
+
+    #[serde(skip)]
+    pub target: PlotTarget,
+
+    #[serde(default)]
+    pub x_time: Option<TimeField>,
+
+    #[serde(default)]
+    pub x_metric: Option<String>,
+
+    /// Comma-separated names of the y-axis metric properties.
+    #[serde(default)]
+    pub y_metrics: Option<String>,
+
+    #[serde(default)]
+    pub series_key: Option<String>,
+
+    #[serde(default)]
+    pub series_by_context: bool,
+
+    #[serde(default)]
+    pub do_plot: bool,
+}
+
+impl PlotLineQuery {
+    fn x_time(&self, field: TimeField) -> Self {
+        let mut this = self.clone();
+        this.x_time = Some(field);
+        this.x_metric = None;
+        this
+    }
+
+    fn x_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.x_time = None;
+        this.x_metric = Some(name.to_owned());
+        this
+    }
+
+    fn add_y_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.y_metrics = add_metric(&self.y_metrics, name);
+        this
+    }
+
+    fn remove_y_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.y_metrics = remove_metric(&self.y_metrics, name);
+        this.do_plot = false;
+        this
+    }
+
+    fn series_key(&self, key: &str) -> Self {
+        let mut this = self.clone();
+        this.series_key = Some(key.to_owned());
+        this.series_by_context = false;
+        this
+    }
+
+    fn series_by_context(&self) -> Self {
+        let mut this = self.clone();
+        this.series_key = None;
+        this.series_by_context = true;
+        this
+    }
+
+    fn item(&self, kind: ItemKind) -> Self {
+        Self {
+            item: kind,
+            target: PlotTarget::new(kind),
+            ..Default::default()
+        }
+    }
+
+    fn do_plot(&self) -> Self {
+        let mut this = self.clone();
+        this.do_plot = true;
+        this
+    }
+
+    fn to_url(&self) -> String {
+        format!(
+            "/plot/line?{}",
+            query::join_qs(&[&self.target.to_qs(), &query::to_qs(self)])
+        )
+    }
+
+    fn x_value(&self, tz: TimeZone, item: &PlotItem) -> Option<XValue> {
+        match (self.x_time, &self.x_metric) {
+            (Some(TimeField::Ctime), _) => Some(XValue::Time(format_plot_time(tz, item.ctime))),
+            (Some(TimeField::Mtime), _) => Some(XValue::Time(format_plot_time(tz, item.mtime))),
+            (None, Some(metric)) => item.get(metric).and_then(get_double).map(XValue::Number),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, serde::Serialize)]
+#[serde(untagged)]
+enum XValue {
+    Number(f64),
+    Time(String),
+}
+
+/// X value, Y value and hover text of a point of a line plot.
+type LinePoint = (XValue, f64, String);
+
+#[get("/plot/line")]
+pub async fn plot_line(
+    config: web::Data<Config>,
+    query: web::Query<PlotLineQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
+    let query = PlotLineQuery {
+        target: PlotTarget::from_request(query.item, &req)?,
+        ..query.into_inner()
+    };
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    let items = query
+        .target
+        .get_items(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut md = format!(
+        "# Line Plot ([target {}s]({}))\n",
+        query.item.to_string().to_lowercase(),
+        query.target.to_url()
+    );
+    md += &item_kind_links_md(query.item, |kind| query.item(kind).to_url());
+
+    if let Some(field) = query.x_time {
+        md += &format!("- **X-axis**: {}\n", field);
+    } else if let Some(metric) = &query.x_metric {
+        md += &format!("- **X-axis metric property**: {}\n", property_label(metric));
+    } else {
+        md += "\nSelect x-axis:\n";
+        md += &format!("- [ctime]({})\n", query.x_time(TimeField::Ctime).to_url());
+        md += &format!("- [mtime]({})\n", query.x_time(TimeField::Mtime).to_url());
+        for c in candidates(&items, is_int) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.x_metric(c).to_url()
+            );
+        }
+        return Ok(response::markdown(&md));
+    }
+
+    let y_metrics = split_metrics(&query.y_metrics);
+    md += &metrics_selector_md(
+        "Y-axis",
+        &items,
+        &y_metrics,
+        |m| query.add_y_metric(m).to_url(),
+        |m| query.remove_y_metric(m).to_url(),
+    );
+    if y_metrics.is_empty() {
+        return Ok(response::markdown(&md));
+    }
+
+    if let Some(key) = &query.series_key {
+        md += &format!("- **Series property**: {}\n", property_label(key));
+    } else if query.series_by_context {
+        md += "- **Series**: context\n";
+    } else {
+        md += "\nSelect series property (optional):\n";
+        if query.item != ItemKind::Context {
+            md += &format!("- [(context)]({})\n", query.series_by_context().to_url());
+        }
+        for c in candidates(&items, |v| get_string(v).is_some()) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.series_key(c).to_url()
+            );
+        }
+    }
+
+    let item_contexts = if query.series_key.is_none() && query.series_by_context {
+        query
+            .target
+            .get_item_contexts(&mut conn, &items)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+    } else {
+        HashMap::new()
+    };
+
+    let mut series: BTreeMap<(String, &str), Vec<LinePoint>> = BTreeMap::new();
+    for a in &items {
+        let x = if let Some(x) = query.x_value(tz, a) {
+            x
+        } else {
+            continue;
+        };
+        let names = if query.series_key.is_some() {
+            vec![series_name(&query.series_key, a).to_owned()]
+        } else if query.series_by_context {
+            item_contexts
+                .get(&a.id)
+                .cloned()
+                .unwrap_or_else(|| vec![String::new()])
+        } else {
+            vec![String::new()]
+        };
+        for metric in &y_metrics {
+            if let Some(y) = a.get(metric).and_then(get_double) {
+                for name in &names {
+                    series.entry((name.clone(), *metric)).or_default().push((
+                        x.clone(),
+                        y,
+                        format!("{}: {}", query.item, a.id),
+                    ));
                 }
             }
-            // continue;
         }
+    }
+
+    if !query.do_plot {
+        md += &format!("- n_series: {}\n", series.len());
+        md += &format!(
+            "- n_points: {}\n",
+            series.values().map(|x| x.len()).sum::<usize>()
+        );
+        md += &format!("\n**[Do plot]({})**\n", query.do_plot().to_url());
+        return Ok(response::markdown(&md));
+    }
+
+    let mut plot = Plot::new();
+    for ((name, metric), mut points) in series {
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        let mut texts = Vec::new();
+        for (x, y, text) in points {
+            xs.push(x);
+            ys.push(y);
+            texts.push(text);
+        }
+        let trace = Scatter::new(xs, ys)
+            .name(&trace_name(&name, metric, y_metrics.len() > 1))
+            .mode(Mode::LinesMarkers)
+            .text_array(texts);
+        plot.add_trace(trace);
+    }
+
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "plot");
+    Ok(response::markdown(&md))
+}
+
+/// Values and hover texts of the samples of a distribution.
+type DistributionSamples = (Vec<f64>, Vec<String>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DistributionStyle {
+    #[default]
+    Box,
+    Violin,
+}
+
+impl DistributionStyle {
+    const ALL: &'static [Self] = &[Self::Box, Self::Violin];
+}
+
+impl std::fmt::Display for DistributionStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Box => write!(f, "box"),
+            Self::Violin => write!(f, "violin"),
+        }
+    }
+}
+
+/// Box or violin trace.
+///
+/// `plotly` 0.6 doesn't provide violin traces, so the trace JSON is built directly.
+#[derive(Debug)]
+struct DistributionTrace(serde_json::Value);
+
+impl DistributionTrace {
+    fn new(style: DistributionStyle, name: &str, ys: Vec<f64>, texts: Vec<String>) -> Box<Self> {
+        let mut trace = serde_json::json!({
+            "type": style.to_string(),
+            "name": name,
+            "y": ys,
+            "text": texts,
+        });
+        match style {
+            DistributionStyle::Box => {
+                trace["boxmean"] = serde_json::json!(true);
+                trace["boxpoints"] = serde_json::json!("all");
+            }
+            DistributionStyle::Violin => {
+                trace["box"] = serde_json::json!({"visible": true});
+                trace["meanline"] = serde_json::json!({"visible": true});
+                trace["points"] = serde_json::json!("all");
+            }
+        }
+        Box::new(Self(trace))
+    }
+}
 
-        for (k, v) in &a.properties {
-            if k != y_metric_property {
-                continue;
+impl plotly::Trace for DistributionTrace {
+    fn serialize(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PlotDistributionQuery {
+    #[serde(default)]
+    pub item: ItemKind,
+
+    #[serde(skip)]
+    pub target: PlotTarget,
+
+    /// Comma-separated names of the metric properties.
+    #[serde(default)]
+    pub metrics: Option<String>,
+
+    #[serde(default)]
+    pub series_key: Option<String>,
+
+    #[serde(default)]
+    pub style: DistributionStyle,
+
+    #[serde(default)]
+    pub do_plot: bool,
+}
+
+impl PlotDistributionQuery {
+    fn add_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.metrics = add_metric(&self.metrics, name);
+        this
+    }
+
+    fn remove_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.metrics = remove_metric(&self.metrics, name);
+        this.do_plot = false;
+        this
+    }
+
+    fn series_key(&self, key: &str) -> Self {
+        let mut this = self.clone();
+        this.series_key = Some(key.to_owned());
+        this
+    }
+
+    fn style(&self, style: DistributionStyle) -> Self {
+        let mut this = self.clone();
+        this.style = style;
+        this
+    }
+
+    fn item(&self, kind: ItemKind) -> Self {
+        Self {
+            item: kind,
+            target: PlotTarget::new(kind),
+            ..Default::default()
+        }
+    }
+
+    fn do_plot(&self) -> Self {
+        let mut this = self.clone();
+        this.do_plot = true;
+        this
+    }
+
+    fn to_url(&self) -> String {
+        format!(
+            "/plot/distribution?{}",
+            query::join_qs(&[&self.target.to_qs(), &query::to_qs(self)])
+        )
+    }
+}
+
+#[get("/plot/distribution")]
+pub async fn plot_distribution(
+    config: web::Data<Config>,
+    query: web::Query<PlotDistributionQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let query = PlotDistributionQuery {
+        target: PlotTarget::from_request(query.item, &req)?,
+        ..query.into_inner()
+    };
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    let items = query
+        .target
+        .get_items(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut md = format!(
+        "# Distribution Plot ([target {}s]({}))\n",
+        query.item.to_string().to_lowercase(),
+        query.target.to_url()
+    );
+    md += &item_kind_links_md(query.item, |kind| query.item(kind).to_url());
+
+    let metrics = split_metrics(&query.metrics);
+    md += &metrics_selector_md(
+        "Value",
+        &items,
+        &metrics,
+        |m| query.add_metric(m).to_url(),
+        |m| query.remove_metric(m).to_url(),
+    );
+    if metrics.is_empty() {
+        return Ok(response::markdown(&md));
+    }
+
+    if let Some(key) = &query.series_key {
+        md += &format!("- **Group property**: {}\n", property_label(key));
+    } else {
+        md += "\nSelect group property (optional):\n";
+        for c in candidates(&items, |v| get_string(v).is_some()) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.series_key(c).to_url()
+            );
+        }
+    }
+
+    md += &format!(
+        "- **Style**: {}\n",
+        DistributionStyle::ALL
+            .iter()
+            .map(|&style| if style == query.style {
+                format!("**{}**", style)
+            } else {
+                format!("[{}]({})", style, query.style(style).to_url())
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+
+    let mut groups: BTreeMap<(&str, &str), DistributionSamples> = BTreeMap::new();
+    for a in &items {
+        for metric in &metrics {
+            if let Some(m) = a.get(metric).and_then(get_double) {
+                let (ys, texts) = groups
+                    .entry((series_name(&query.series_key, a), *metric))
+                    .or_default();
+                ys.push(m);
+                texts.push(format!("{}: {}", query.item, a.id));
             }
-            if let Some(m) = get_double(v) {
-                ys.insert(artifact_id, m);
-                break;
+        }
+    }
+
+    if !query.do_plot {
+        md += &format!(
+            "- n_target: {}\n",
+            groups.values().map(|x| x.0.len()).sum::<usize>()
+        );
+        md += &format!("- n_groups: {}\n", groups.len());
+        md += &format!("\n**[Do plot]({})**\n", query.do_plot().to_url());
+        return Ok(response::markdown(&md));
+    }
+
+    let mut plot = Plot::new();
+    for ((name, metric), (ys, texts)) in groups {
+        plot.add_trace(DistributionTrace::new(
+            query.style,
+            &trace_name(name, metric, metrics.len() > 1),
+            ys,
+            texts,
+        ));
+    }
+
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "plot");
+    Ok(response::markdown(&md))
+}
+
+/// Parallel coordinates trace.
+///
+/// `plotly` 0.6 doesn't provide parallel coordinates traces, so the trace JSON is built directly.
+#[derive(Debug)]
+struct ParallelTrace(serde_json::Value);
+
+impl ParallelTrace {
+    fn new(dimensions: Vec<serde_json::Value>, color: Option<(&str, Vec<f64>)>) -> Box<Self> {
+        let mut trace = serde_json::json!({
+            "type": "parcoords",
+            "dimensions": dimensions,
+        });
+        if let Some((name, colors)) = color {
+            trace["line"] = serde_json::json!({
+                "color": colors,
+                "colorscale": "Viridis",
+                "showscale": true,
+                "colorbar": {"title": name},
+            });
+        }
+        Box::new(Self(trace))
+    }
+}
+
+impl plotly::Trace for ParallelTrace {
+    fn serialize(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Axis of a parallel coordinates plot.
+#[derive(Debug)]
+enum Dimension<'a> {
+    Numeric(Vec<f64>),
+    Categorical(Vec<&'a str>),
+}
+
+impl Dimension<'_> {
+    fn to_json(&self, label: &str) -> serde_json::Value {
+        match self {
+            Self::Numeric(values) => serde_json::json!({
+                "label": label,
+                "values": values,
+            }),
+            Self::Categorical(values) => {
+                let categories = values.iter().copied().collect::<BTreeSet<_>>();
+                let codes = values
+                    .iter()
+                    .map(|v| categories.iter().position(|c| c == v).expect("unreachable"))
+                    .collect::<Vec<_>>();
+                serde_json::json!({
+                    "label": label,
+                    "values": codes,
+                    "tickvals": (0..categories.len()).collect::<Vec<_>>(),
+                    "ticktext": categories,
+                })
             }
         }
     }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PlotParallelQuery {
+    #[serde(default)]
+    pub item: ItemKind,
+
+    #[serde(skip)]
+    pub target: PlotTarget,
+
+    /// Comma-separated names of the properties used as axes.
+    #[serde(default)]
+    pub dimensions: Option<String>,
+
+    #[serde(default)]
+    pub color_metric: Option<String>,
+
+    #[serde(default)]
+    pub do_plot: bool,
+}
+
+impl PlotParallelQuery {
+    fn add_dimension(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.dimensions = add_metric(&self.dimensions, name);
+        this
+    }
+
+    fn remove_dimension(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.dimensions = remove_metric(&self.dimensions, name);
+        this.do_plot = false;
+        this
+    }
+
+    fn color_metric(&self, name: &str) -> Self {
+        let mut this = self.clone();
+        this.color_metric = Some(name.to_owned());
+        this
+    }
+
+    fn item(&self, kind: ItemKind) -> Self {
+        Self {
+            item: kind,
+            target: PlotTarget::new(kind),
+            ..Default::default()
+        }
+    }
+
+    fn do_plot(&self) -> Self {
+        let mut this = self.clone();
+        this.do_plot = true;
+        this
+    }
+
+    fn to_url(&self) -> String {
+        format!(
+            "/plot/parallel?{}",
+            query::join_qs(&[&self.target.to_qs(), &query::to_qs(self)])
+        )
+    }
+}
+
+#[get("/plot/parallel")]
+pub async fn plot_parallel(
+    config: web::Data<Config>,
+    query: web::Query<PlotParallelQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let query = PlotParallelQuery {
+        target: PlotTarget::from_request(query.item, &req)?,
+        ..query.into_inner()
+    };
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    let items = query
+        .target
+        .get_items(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut md = format!(
+        "# Parallel Coordinates Plot ([target {}s]({}))\n",
+        query.item.to_string().to_lowercase(),
+        query.target.to_url()
+    );
+    md += &item_kind_links_md(query.item, |kind| query.item(kind).to_url());
+
+    let dimensions = split_metrics(&query.dimensions);
+    if !dimensions.is_empty() {
+        md += &format!(
+            "- **Axis properties**: {}\n",
+            dimensions
+                .iter()
+                .map(|d| format!(
+                    "{} [x]({})",
+                    property_label(d),
+                    query.remove_dimension(d).to_url()
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let candidates_md = candidates(&items, |v| is_int_or_double(v) || get_string(v).is_some())
+        .into_iter()
+        .filter(|c| !dimensions.contains(c))
+        .map(|c| {
+            format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.add_dimension(c).to_url()
+            )
+        })
+        .collect::<String>();
+    if !candidates_md.is_empty() {
+        if dimensions.len() < 2 {
+            md += "\nSelect axis property:\n";
+        } else {
+            md += "\nAdd axis property (optional):\n";
+        }
+        md += &candidates_md;
+    }
+    if dimensions.len() < 2 {
+        return Ok(response::markdown(&md));
+    }
 
-    let mut xs1 = Vec::new();
-    let mut ys1 = Vec::new();
-    let mut texts = Vec::new();
-    for (id, x) in &xs {
-        if let Some(y) = ys.get(id) {
-            xs1.push(*x);
-            ys1.push(*y);
-            texts.push(format!("Artifact: {}", id));
+    if let Some(metric) = &query.color_metric {
+        md += &format!("- **Color metric property**: {}\n", property_label(metric));
+    } else {
+        md += "\nSelect color metric property (optional):\n";
+        for c in candidates(&items, is_int_or_double) {
+            md += &format!(
+                "- [{}]({})\n",
+                property_label(c),
+                query.color_metric(c).to_url()
+            );
         }
     }
 
+    // Items that lack some of the selected properties are skipped because every axis needs a value.
+    let is_numeric = dimensions
+        .iter()
+        .map(|d| items.iter().filter_map(|a| a.get(d)).all(is_int_or_double))
+        .collect::<Vec<_>>();
+    let mut axes = is_numeric
+        .iter()
+        .map(|&numeric| {
+            if numeric {
+                Dimension::Numeric(Vec::new())
+            } else {
+                Dimension::Categorical(Vec::new())
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut colors = Vec::new();
+    let mut n_skipped = 0;
+    for a in &items {
+        let color = match &query.color_metric {
+            Some(metric) => match a.get(metric).and_then(get_double) {
+                Some(v) => Some(v),
+                None => {
+                    n_skipped += 1;
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let values = dimensions
+            .iter()
+            .map(|d| a.get(d))
+            .collect::<Option<Vec<_>>>();
+        let values = if let Some(values) = values {
+            values
+        } else {
+            n_skipped += 1;
+            continue;
+        };
+        for (axis, v) in axes.iter_mut().zip(values) {
+            match axis {
+                Dimension::Numeric(xs) => xs.push(get_double(v).expect("unreachable")),
+                Dimension::Categorical(xs) => xs.push(get_string(v).unwrap_or("")),
+            }
+        }
+        colors.extend(color);
+    }
+    let n_plotted = items.len() - n_skipped;
+
     if !query.do_plot {
-        md += &format!("- n_target: {}\n", xs1.len());
+        md += &format!("- n_target: {}\n", n_plotted);
+        md += &format!("- n_skipped: {}\n", n_skipped);
         md += &format!("\n**[Do plot]({})**\n", query.do_plot().to_url());
         return Ok(response::markdown(&md));
     }
 
+    let dimensions = dimensions
+        .iter()
+        .zip(&axes)
+        .map(|(d, axis)| axis.to_json(&property_label(d)))
+        .collect();
+    let color = query
+        .color_metric
+        .as_ref()
+        .map(|metric| (metric.as_str(), colors));
     let mut plot = Plot::new();
-    let trace = Scatter::new(xs1, ys1).mode(Mode::Markers).text_array(texts);
-    plot.add_trace(trace);
-
-    let mut html = String::new();
-    let mut html_file = tempfile::NamedTempFile::new()?;
-    plot.to_html(html_file.path());
-    html_file.read_to_string(&mut html)?;
-    Ok(response::html(&html))
+    plot.add_trace(ParallelTrace::new(dimensions, color));
+
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "plot");
+    Ok(response::markdown(&md))
+}
+
+pub fn format_plot_time(tz: TimeZone, t: DateTime) -> String {
+    tz.convert(t).format("%Y-%m-%d %H:%M:%S%.3f").to_string()
 }
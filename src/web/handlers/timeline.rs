@@ -0,0 +1,206 @@
+use crate::graph;
+use crate::mlmd::execution::{Execution, ExecutionOrderByField, ExecutionState};
+use crate::time::{DateTime, Period, TimeZone};
+use crate::web::handlers::executions::GetExecutionsQuery;
+use crate::web::handlers::plot::format_plot_time;
+use crate::web::{response, time_range, views, Config};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use plotly::common::{Line, Mode};
+use plotly::{Plot, Scatter};
+use std::collections::BTreeMap;
+
+/// Default maximum number of executions shown in a timeline.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// X values, Y values and hover texts of the bars of a timeline trace.
+type TraceData = (Vec<Option<String>>, Vec<Option<String>>, Vec<String>);
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetTimelineQuery {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(
+        default,
+        deserialize_with = "crate::time::deserialize_datetime_param",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mtime_start: Option<DateTime>,
+    #[serde(
+        default,
+        deserialize_with = "crate::time::deserialize_datetime_param",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mtime_end: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime_last: Option<Period>,
+    /// Time zone that overrides the `weih-tz` cookie (only kept so that the links carry it over).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz: Option<TimeZone>,
+}
+
+impl GetTimelineQuery {
+    fn mtime_range(&self, start: Option<DateTime>, end: Option<DateTime>) -> Self {
+        let mut this = self.clone();
+        this.mtime_start = start;
+        this.mtime_end = end;
+        this.mtime_last = None;
+        this
+    }
+
+    fn mtime_last(&self, period: Period) -> Self {
+        let mut this = self.clone();
+        this.mtime_start = None;
+        this.mtime_end = None;
+        this.mtime_last = Some(period);
+        this
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+
+    fn to_url(&self) -> String {
+        format!("/timeline/?{}", crate::web::query::to_qs(self))
+    }
+
+    fn executions(&self) -> GetExecutionsQuery {
+        GetExecutionsQuery {
+            type_name: self.type_name.clone(),
+            context: self.context,
+            limit: Some(self.limit()),
+            order_by: ExecutionOrderByField::UpdateTime,
+            mtime_start: self.mtime_start,
+            mtime_end: self.mtime_end,
+            mtime_last: self.mtime_last,
+            tz: self.tz,
+            ..Default::default()
+        }
+    }
+}
+
+#[get("/timeline/")]
+pub async fn get_timeline(
+    config: web::Data<Config>,
+    query: web::Query<GetTimelineQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let tz = config.time_zone(&req);
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+
+    let executions = query
+        .executions()
+        .get_executions(&mut store, &mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let execution_types = query
+        .executions()
+        .get_execution_types(&mut store, &executions)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let filter_md = time_range::mtime_range_filter(
+        tz,
+        query.mtime_start,
+        query.mtime_end,
+        query.mtime_last,
+        |start, end| query.mtime_range(start, end).to_url(),
+        |period| query.mtime_last(period).to_url(),
+    );
+    if executions.is_empty() {
+        let md = format!(
+            "# Timeline\n\n{}\nNo [target executions]({}).\n",
+            filter_md,
+            query.executions().to_url()
+        );
+        return Ok(response::markdown(&md));
+    }
+
+    let truncated = executions.len() >= query.limit();
+    let mut executions = executions
+        .into_iter()
+        .map(|x| Execution::from((execution_types[&x.type_id].clone(), x)))
+        .collect::<Vec<_>>();
+    let oldest_mtime = executions.iter().map(|e| e.mtime).min();
+    executions.sort_by(|a, b| (&a.type_name, a.ctime).cmp(&(&b.type_name, b.ctime)));
+
+    // Executions that are still in progress are drawn up to now so that stuck runs stand out.
+    let now = chrono::Local::now();
+    let mut traces: BTreeMap<ExecutionState, TraceData> = BTreeMap::new();
+    for e in &executions {
+        let end = if matches!(e.state, ExecutionState::New | ExecutionState::Running) {
+            now
+        } else {
+            e.mtime
+        };
+        let row = format!("{} #{}", e.type_name, e.id);
+        let text = format!(
+            "{}<br>{}<br>{} ~ {}",
+            row,
+            e.name.as_ref().map_or("", |x| x.as_str()),
+            tz.convert(e.ctime),
+            tz.convert(e.mtime)
+        );
+
+        let (xs, ys, texts) = traces.entry(e.state).or_default();
+        xs.push(Some(format_plot_time(tz, e.ctime)));
+        xs.push(Some(format_plot_time(tz, end)));
+        xs.push(None);
+        ys.push(Some(row.clone()));
+        ys.push(Some(row));
+        ys.push(None);
+        texts.push(text.clone());
+        texts.push(text);
+        texts.push(String::new());
+    }
+
+    let mut plot = Plot::new();
+    for (state, (xs, ys, texts)) in traces {
+        let trace = Scatter::new(xs, ys)
+            .name(&state.to_string())
+            .mode(Mode::Lines)
+            .line(Line::new().width(10.0).color(state_color(state)))
+            .text_array(texts);
+        plot.add_trace(trace);
+    }
+
+    let mut md = format!(
+        "# Timeline ([target executions]({}))\n",
+        query.executions().to_url()
+    );
+    md += &format!("\n{}", filter_md);
+    if let (true, Some(oldest_mtime)) = (truncated, oldest_mtime) {
+        // The executions are fetched in descending order of update time,
+        // so the next range ends at (and excludes) the oldest shown one.
+        let start = query
+            .mtime_start
+            .or_else(|| query.mtime_last.and_then(Period::start));
+        md += &format!(
+            "\nOnly the {} most recently updated executions are shown ([older executions]({})).\n",
+            query.limit(),
+            query.mtime_range(start, Some(oldest_mtime)).to_url()
+        );
+    }
+    md += &format!("\n{}\n", views::save_link_md(&req));
+    md += &response::plot_html(&plot, "timeline");
+    Ok(response::markdown(&md))
+}
+
+/// Returns the color of the bars of executions in `state`.
+///
+/// The states that stand out in graphs have the same colors here.
+fn state_color(state: ExecutionState) -> &'static str {
+    if let Some((color, _)) = graph::execution_state_colors(state) {
+        return color;
+    }
+    match state {
+        ExecutionState::Complete => "#38761d",
+        ExecutionState::Cached => "#93c47d",
+        ExecutionState::New => "#999999",
+        _ => "#666666",
+    }
+}
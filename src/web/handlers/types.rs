@@ -0,0 +1,70 @@
+use crate::graph::{GraphFormat, TypeGraph};
+use crate::mlmd::type_graph;
+use crate::web::{response, Config};
+use actix_web::{get, web, HttpResponse};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GetTypeGraphQuery {
+    pub format: Option<GraphFormat>,
+}
+
+#[get("/types/graph")]
+pub async fn get_type_graph(
+    config: web::Data<Config>,
+    query: web::Query<GetTypeGraphQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let mut store = config.connect_metadata_store().await?;
+    let mut conn = config.connect_database().await?;
+    let counts = type_graph::get_type_event_counts(&mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let graph = TypeGraph::new(&mut store, &counts)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if let Some(format) = query.format {
+        let body = graph
+            .to_bytes(format)
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        return Ok(response::bytes(format.content_type(), body));
+    }
+
+    let mut md = "# Type Graph\n\n".to_string();
+    md += "Artifact types (ellipses) and execution types (boxes) connected by events. ";
+    md += "Edge labels are the numbers of the events.\n\n";
+    md += &format!(
+        "Download: {}\n\n",
+        GraphFormat::ALL
+            .iter()
+            .map(|f| format!("[{}](/types/graph?format={})", f, f))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if graph.nodes.is_empty() {
+        md += "No events.\n";
+        return Ok(response::markdown(&md));
+    }
+    match graph.to_bytes(GraphFormat::Svg) {
+        Ok(svg) => {
+            let svg = String::from_utf8(svg).map_err(actix_web::error::ErrorInternalServerError)?;
+            // Drops the XML declaration and DOCTYPE so that the SVG can be embedded in HTML,
+            // and blank lines so that the SVG isn't split into markdown paragraphs.
+            let svg = svg.find("<svg").map_or(svg.as_str(), |i| &svg[i..]);
+            let svg = svg
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+            md += &format!("<div>\n{}\n</div>\n", svg);
+        }
+        Err(_) => {
+            let dot = graph
+                .to_bytes(GraphFormat::Dot)
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+            let dot = String::from_utf8(dot).map_err(actix_web::error::ErrorInternalServerError)?;
+            md += &format!("```dot\n{}```\n", dot);
+        }
+    }
+    Ok(response::markdown(&md))
+}
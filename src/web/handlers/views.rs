@@ -0,0 +1,159 @@
+use crate::web::{response, views, Config};
+use actix_web::{get, post, web, HttpResponse};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NewViewQuery {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SaveViewForm {
+    pub name: String,
+    pub url: String,
+}
+
+#[get("/views/")]
+async fn get_views(config: web::Data<Config>) -> actix_web::Result<HttpResponse> {
+    let mut md = "# Saved Views\n".to_string();
+    if !config.views().is_persistent() {
+        md += "\nViews are not persisted because `--views-file` isn't specified.\n";
+    }
+
+    let views = config.views().list();
+    if views.is_empty() {
+        md += "\nNo saved views. Use the \"Save view\" link on list and plot pages.\n";
+        return Ok(response::markdown(&md));
+    }
+
+    md += "\n| id | name | url | |\n";
+    md += "|----|------|-----|-|\n";
+    for v in views {
+        md += &format!(
+            "| [{}]({}) | [{}]({}) | {} | <form method='post' action='/views/{}/delete'><button>Delete</button></form> |\n",
+            v.id,
+            v.permalink(),
+            escape_md(&v.name),
+            v.permalink(),
+            escape_md(&v.url),
+            v.id
+        );
+    }
+    Ok(response::markdown(&md))
+}
+
+#[get("/views/new")]
+async fn new_view(query: web::Query<NewViewQuery>) -> actix_web::Result<HttpResponse> {
+    if !views::is_weih_url(&query.url) {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "not a weih URL: {:?}",
+            query.url
+        )));
+    }
+
+    let md = format!(
+        r#"# Save View
+
+- **URL**: [{}]({})
+
+<form method='post' action='/views/'>
+<input type='hidden' name='url' value='{}'>
+<input type='text' name='name' placeholder='name' required autofocus>
+<button>Save</button>
+</form>
+"#,
+        escape_md(&query.url),
+        escape_link(&query.url),
+        escape_html(&query.url)
+    );
+    Ok(response::markdown(&md))
+}
+
+#[post("/views/")]
+async fn save_view(
+    config: web::Data<Config>,
+    form: web::Form<SaveViewForm>,
+) -> actix_web::Result<HttpResponse> {
+    config
+        .views()
+        .add(&form.name, &form.url)
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    Ok(response::see_other("/views/"))
+}
+
+#[post("/views/{id}/delete")]
+async fn delete_view(
+    config: web::Data<Config>,
+    path: web::Path<(u32,)>,
+) -> actix_web::Result<HttpResponse> {
+    let (id,) = path.into_inner();
+    config
+        .views()
+        .remove(id)
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound(format!("no such view: {}", id)))?;
+    Ok(response::see_other("/views/"))
+}
+
+#[get("/v/{id}")]
+async fn get_view(
+    config: web::Data<Config>,
+    path: web::Path<(u32,)>,
+) -> actix_web::Result<HttpResponse> {
+    let (id,) = path.into_inner();
+    let view = config
+        .views()
+        .get(id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound(format!("no such view: {}", id)))?;
+    Ok(response::redirect(&view.url))
+}
+
+/// Makes the markdown of the saved views shown on the index page.
+pub fn views_md(config: &Config) -> String {
+    let views = config.views().list();
+    if views.is_empty() {
+        return String::new();
+    }
+
+    let mut md = "\n## [Saved Views](/views/)\n\n".to_string();
+    for v in views {
+        md += &format!("- [{}]({})\n", escape_md(&v.name), v.permalink());
+    }
+    md
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&#39;")
+        .replace('"', "&quot;")
+}
+
+/// Percent-encodes the characters that would end or break a markdown link destination.
+fn escape_link(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        if c.is_ascii_graphic() && !matches!(c, '(' | ')' | '<' | '>' | '\\' | '`' | '"' | '\'') {
+            escaped.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                escaped += &format!("%{:02X}", b);
+            }
+        }
+    }
+    escaped
+}
+
+fn escape_md(s: &str) -> String {
+    escape_html(s)
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace('|', "\\|")
+        .replace('`', "\\`")
+        .replace('*', "\\*")
+        .replace('_', "\\_")
+}
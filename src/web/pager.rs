@@ -0,0 +1,71 @@
+pub const PAGE_SIZES: &[usize] = &[20, 50, 100, 500];
+
+/// Makes the markdown of a pager.
+///
+/// `prev_next_urls` are the URLs of the previous and the next pages (which may resume from keyset cursors),
+/// and `page_url(offset)` and `limit_url(limit)` should return the URL of the page starting from `offset`
+/// and the URL of the first page with `limit` items per page, respectively.
+pub fn pager_md<F, G>(
+    offset: usize,
+    limit: usize,
+    len: usize,
+    total: usize,
+    (prev_url, next_url): (&str, &str),
+    page_url: F,
+    limit_url: G,
+) -> String
+where
+    F: Fn(usize) -> String,
+    G: Fn(usize) -> String,
+{
+    let mut md = String::new();
+    if offset != 0 {
+        md += &format!(" [<<]({})", prev_url);
+    } else {
+        md += " <<";
+    }
+    if len == 0 {
+        md += &format!(" 0 of {} ", total);
+    } else {
+        md += &format!(" {}~{} of {} ", offset + 1, offset + len, total);
+    }
+    if offset + len < total {
+        md += &format!("[>>]({})", next_url);
+    } else {
+        md += ">>";
+    }
+
+    let limit = limit.max(1);
+    let pages = total.div_ceil(limit);
+    let current = offset / limit;
+    if pages > 1 {
+        md += " | Page:";
+        let mut last_shown = None;
+        for page in 0..pages {
+            let is_edge = page == 0 || page + 1 == pages;
+            let is_near = page + 2 >= current && page <= current + 2;
+            if !is_edge && !is_near {
+                continue;
+            }
+            if last_shown.is_some_and(|p| p + 1 != page) {
+                md += " …";
+            }
+            if page == current {
+                md += &format!(" **{}**", page + 1);
+            } else {
+                md += &format!(" [{}]({})", page + 1, page_url(page * limit));
+            }
+            last_shown = Some(page);
+        }
+    }
+
+    md += " | Per page:";
+    for n in PAGE_SIZES {
+        if *n == limit {
+            md += &format!(" **{}**", n);
+        } else {
+            md += &format!(" [{}]({})", n, limit_url(*n));
+        }
+    }
+    md
+}
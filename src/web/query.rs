@@ -0,0 +1,369 @@
+use crate::hook::ItemType;
+use crate::mlmd::artifact::ArtifactOrderByField;
+use crate::mlmd::context::ContextOrderByField;
+use crate::mlmd::event::EventOrderByField;
+use crate::mlmd::execution::ExecutionOrderByField;
+use crate::mlmd::hierarchy::ContextHierarchy;
+use crate::mlmd::list::{self, Cursor, ItemFilter, Keyset, OrderColumn};
+use crate::time::{DateTime, Period, TimeZone};
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::time::Duration;
+
+/// Field to order the items of a list page by.
+pub trait OrderByField: Copy + Default + PartialEq + serde::Serialize {
+    /// Path of the list page.
+    const PATH: &'static str;
+
+    fn column(self) -> OrderColumn;
+}
+
+impl OrderByField for ArtifactOrderByField {
+    const PATH: &'static str = "/artifacts/";
+
+    fn column(self) -> OrderColumn {
+        match self {
+            Self::Id => OrderColumn::Id,
+            Self::Name => OrderColumn::Name,
+            Self::CreateTime => OrderColumn::CreateTime,
+            Self::UpdateTime => OrderColumn::UpdateTime,
+        }
+    }
+}
+
+impl OrderByField for ExecutionOrderByField {
+    const PATH: &'static str = "/executions/";
+
+    fn column(self) -> OrderColumn {
+        match self {
+            Self::Id => OrderColumn::Id,
+            Self::Name => OrderColumn::Name,
+            Self::CreateTime => OrderColumn::CreateTime,
+            Self::UpdateTime => OrderColumn::UpdateTime,
+        }
+    }
+}
+
+impl OrderByField for ContextOrderByField {
+    const PATH: &'static str = "/contexts/";
+
+    fn column(self) -> OrderColumn {
+        match self {
+            Self::Id => OrderColumn::Id,
+            Self::Name => OrderColumn::Name,
+            Self::CreateTime => OrderColumn::CreateTime,
+            Self::UpdateTime => OrderColumn::UpdateTime,
+        }
+    }
+}
+
+impl OrderByField for EventOrderByField {
+    const PATH: &'static str = "/events/";
+
+    fn column(self) -> OrderColumn {
+        match self {
+            Self::CreateTime => OrderColumn::CreateTime,
+        }
+    }
+}
+
+/// Query parameters of the list pages (`/artifacts/`, `/executions/`, `/contexts/` and `/events/`).
+///
+/// Filters that don't make sense for a list (e.g., `context` for `/contexts/`) are just ignored.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListQuery<F> {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<i32>,
+    /// If `true`, `context` also matches the items in the descendants of the context.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub descendants: bool,
+    /// Matches the items in any context of this type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(default)]
+    pub order_by: F,
+    #[serde(default)]
+    pub asc: bool,
+    #[serde(
+        default,
+        deserialize_with = "crate::time::deserialize_datetime_param",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mtime_start: Option<DateTime>,
+    #[serde(
+        default,
+        deserialize_with = "crate::time::deserialize_datetime_param",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mtime_end: Option<DateTime>,
+    /// Matches the items updated within this period before each request (e.g., `24h`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime_last: Option<Period>,
+    /// Position right after the last item of the previous page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<Cursor>,
+    /// Position right before the first item of the next page (used to go back to the previous page).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Cursor>,
+    /// Time zone that overrides the `weih-tz` cookie (see [`crate::web::Config::time_zone`]).
+    ///
+    /// This is only kept here so that the links of a page carry it over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz: Option<TimeZone>,
+
+    /// `context` and its descendants (see [`ListQuery::resolve_context_filters`]).
+    #[serde(skip)]
+    pub contexts: Option<Vec<i32>>,
+}
+
+impl<F: OrderByField> ListQuery<F> {
+    pub fn page(&self, offset: usize) -> Self {
+        let mut this = self.clone();
+        this.offset = Some(offset);
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn per_page(&self, limit: usize) -> Self {
+        let mut this = self.clone();
+        this.limit = Some(limit);
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    /// Returns the query for all the matching items (i.e., without the pagination parameters).
+    pub fn all(&self) -> Self {
+        let mut this = self.clone();
+        this.limit = None;
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn mtime_range(&self, start: Option<DateTime>, end: Option<DateTime>) -> Self {
+        let mut this = self.clone();
+        this.mtime_start = start;
+        this.mtime_end = end;
+        this.mtime_last = None;
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn mtime_last(&self, period: Period) -> Self {
+        let mut this = self.clone();
+        this.mtime_start = None;
+        this.mtime_end = None;
+        this.mtime_last = Some(period);
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_type(&self, type_name: &str) -> Self {
+        let mut this = self.clone();
+        this.type_name = Some(type_name.to_owned());
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_context(&self, id: i32) -> Self {
+        let mut this = self.clone();
+        this.context = Some(id);
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_descendants(&self, descendants: bool) -> Self {
+        let mut this = self.clone();
+        this.descendants = descendants;
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_context_type(&self, type_name: Option<&str>) -> Self {
+        let mut this = self.clone();
+        this.context_type = type_name.map(|x| x.to_owned());
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_execution(&self, id: i32) -> Self {
+        let mut this = self.clone();
+        this.execution = Some(id);
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn filter_artifact(&self, id: i32) -> Self {
+        let mut this = self.clone();
+        this.artifact = Some(id);
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    pub fn order_by(&self, field: F, asc: bool) -> Self {
+        let mut this = self.clone();
+        this.order_by = field;
+        this.asc = asc;
+        this.offset = None;
+        this.cursor = None;
+        this.before = None;
+        this
+    }
+
+    /// Resolves `context` with `descendants` into the IDs of the context and its descendants.
+    pub async fn resolve_context_filters(
+        &mut self,
+        conn: &mut sqlx::AnyConnection,
+        item_type: ItemType,
+    ) -> anyhow::Result<()> {
+        if item_type == ItemType::Context || !self.descendants {
+            return Ok(());
+        }
+        if let Some(context) = self.context {
+            let hierarchy =
+                ContextHierarchy::load_descendants(conn, &[context], usize::MAX).await?;
+            self.contexts = Some(hierarchy.descendants(context));
+        }
+        Ok(())
+    }
+
+    /// Returns the conditions of the items of `item_type` that this query matches.
+    pub fn item_filter(&self, item_type: ItemType) -> ItemFilter {
+        let mut filter = ItemFilter {
+            type_name: self.type_name.clone(),
+            // Names are unique only within a type.
+            name: self.type_name.as_ref().and(self.name.clone()),
+            update_time: self.update_time(),
+            ..Default::default()
+        };
+        if item_type == ItemType::Context {
+            filter.artifact = self.artifact;
+            filter.execution = self.execution;
+        } else {
+            filter.contexts = self
+                .contexts
+                .clone()
+                .or_else(|| self.context.map(|x| vec![x]));
+            filter.context_type = self.context_type.clone();
+        }
+        filter
+    }
+
+    /// Gets the IDs of the items of `item_type` in the page specified by this query.
+    pub async fn get_item_ids(
+        &self,
+        conn: &mut sqlx::AnyConnection,
+        item_type: ItemType,
+    ) -> anyhow::Result<Vec<i32>> {
+        list::get_item_ids(
+            conn,
+            item_type,
+            &self.item_filter(item_type),
+            (self.order_by.column(), self.asc),
+            self.keyset(),
+            self.offset(),
+            self.limit(),
+        )
+        .await
+    }
+
+    /// Counts the items of `item_type` matching this query regardless of the pagination parameters.
+    pub async fn count_items(
+        &self,
+        conn: &mut sqlx::AnyConnection,
+        item_type: ItemType,
+    ) -> anyhow::Result<usize> {
+        list::count_items(conn, item_type, &self.item_filter(item_type)).await
+    }
+
+    pub fn to_url(&self) -> String {
+        format!("{}?{}", F::PATH, self.to_qs())
+    }
+
+    pub fn to_qs(&self) -> String {
+        to_qs(self)
+    }
+
+    fn keyset(&self) -> Option<Keyset<'_>> {
+        match (&self.cursor, &self.before) {
+            (Some(cursor), _) => Some(Keyset::After(cursor)),
+            (None, Some(cursor)) => Some(Keyset::Before(cursor)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset.unwrap_or(0)
+    }
+
+    /// Returns the page size, which is capped so that the IDs of a page can be fetched at once.
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(100).min(list::BATCH_SIZE)
+    }
+
+    /// Returns the range of the update time specified by `mtime-start`, `mtime-end` and `mtime-last`.
+    pub fn update_time(&self) -> (Bound<Duration>, Bound<Duration>) {
+        let start = match self.mtime_last.and_then(|x| x.start()) {
+            Some(t) => Some(self.mtime_start.map_or(t, |x| x.max(t))),
+            None => self.mtime_start,
+        };
+        crate::time::datetime_range(start, self.mtime_end)
+    }
+}
+
+/// Sorts `items` in the order of `ids` (the `mlmd` crate doesn't keep the order of requested IDs).
+pub fn sort_by_ids<T>(items: &mut [T], ids: &[i32], id: impl Fn(&T) -> i32) {
+    let positions = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect::<HashMap<_, _>>();
+    items.sort_by_key(|x| positions.get(&id(x)).copied().unwrap_or(usize::MAX));
+}
+
+/// Encodes `params` as a query string (`application/x-www-form-urlencoded`).
+pub fn to_qs<T: serde::Serialize>(params: &T) -> String {
+    serde_urlencoded::to_string(params).expect("unreachable")
+}
+
+/// Concatenates query strings, skipping empty ones.
+pub fn join_qs(qss: &[&str]) -> String {
+    qss.iter()
+        .filter(|qs| !qs.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("&")
+}
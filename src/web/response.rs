@@ -14,6 +14,12 @@ pub fn css(s: &str) -> HttpResponse {
         .body(s.to_string())
 }
 
+pub fn js(s: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/javascript")
+        .body(s.to_string())
+}
+
 pub fn html(s: &str) -> HttpResponse {
     HttpResponse::Ok()
         .content_type("text/html")
@@ -32,12 +38,35 @@ pub fn svg(s: &str) -> HttpResponse {
         .body(s.to_string())
 }
 
+pub fn bytes(content_type: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok().content_type(content_type).body(body)
+}
+
 pub fn redirect(url: &str) -> HttpResponse {
     HttpResponse::build(actix_web::http::StatusCode::TEMPORARY_REDIRECT)
         .append_header(("Location", url))
         .finish()
 }
 
+pub fn see_other(url: &str) -> HttpResponse {
+    HttpResponse::build(actix_web::http::StatusCode::SEE_OTHER)
+        .append_header(("Location", url))
+        .finish()
+}
+
+/// Makes the HTML snippet to embed `plot` in a markdown page.
+///
+/// Blank lines are removed so that the snippet isn't split into markdown paragraphs.
+pub fn plot_html(plot: &plotly::Plot, id: &'static str) -> String {
+    let html = plot.to_inline_html(Some(id));
+    let html = html
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("\n<script src='/js/plotly.min.js'></script>\n{}\n\n", html)
+}
+
 fn md_to_html(md: &str) -> String {
     let mut opt = comrak::ComrakOptions::default();
     opt.extension.table = true;
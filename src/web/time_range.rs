@@ -0,0 +1,121 @@
+use crate::time::{DateTime, Period, TimeZone};
+use crate::web::TIME_ZONE_COOKIE;
+
+const RELATIVE_RANGES: &[&str] = &["1h", "24h", "7d", "30d"];
+
+const TIME_ZONE_CANDIDATES: &[&str] = &[
+    "-12:00", "-11:00", "-10:00", "-09:00", "-08:00", "-07:00", "-06:00", "-05:00", "-04:00",
+    "-03:00", "-02:00", "-01:00", "+01:00", "+02:00", "+03:00", "+04:00", "+05:00", "+05:30",
+    "+06:00", "+07:00", "+08:00", "+09:00", "+09:30", "+10:00", "+11:00", "+12:00", "+13:00",
+    "+14:00",
+];
+
+/// Makes the markdown of a date-time range filter
+/// for the `mtime-start`, `mtime-end` and `mtime-last` query parameters.
+///
+/// `url(start, end)` and `last_url(period)` should return the URL of the current page
+/// with the given absolute range and the given relative range, respectively.
+pub fn mtime_range_filter<F, G>(
+    tz: TimeZone,
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+    last: Option<Period>,
+    url: F,
+    last_url: G,
+) -> String
+where
+    F: Fn(Option<DateTime>, Option<DateTime>) -> String,
+    G: Fn(Period) -> String,
+{
+    let mut md = format!(
+        r#"Update Time: <input type="datetime-local" id="mtime_start" step="1" {} onchange="filter_mtime('mtime_start', mtime_start_url, 'mtime-start')"> ~
+             <input type="datetime-local" id="mtime_end" step="1" {} onchange="filter_mtime('mtime_end', mtime_end_url, 'mtime-end')">
+"#,
+        input_value(tz, start),
+        input_value(tz, end)
+    );
+
+    md += "(last:";
+    for period in RELATIVE_RANGES {
+        let period: Period = period.parse().expect("unreachable");
+        if last == Some(period) {
+            md += &format!(" **{}**", period);
+        } else {
+            md += &format!(" [{}]({})", period, last_url(period));
+        }
+    }
+    if start.is_some() || end.is_some() || last.is_some() {
+        md += &format!(" | [clear]({})", url(None, None));
+    }
+    md += "),\n";
+
+    md += r#"Time Zone: <select onchange="set_time_zone(this.value)">"#;
+    let mut candidates = vec!["local".to_owned(), "utc".to_owned(), "browser".to_owned()];
+    candidates.extend(TIME_ZONE_CANDIDATES.iter().map(|x| x.to_string()));
+    if !candidates.contains(&tz.to_string()) {
+        candidates.push(tz.to_string());
+    }
+    for c in candidates {
+        md += &format!(
+            r#"<option value="{}" {}>{}</option>"#,
+            c,
+            if c == tz.to_string() { "selected" } else { "" },
+            c
+        );
+    }
+    md += "</select>\n";
+
+    md += &format!(
+        r#"
+<script type="text/javascript">
+var mtime_start_url = {};
+var mtime_end_url = {};
+function filter_mtime(id, url, param) {{
+  var v = document.getElementById(id).value;
+  if (v == "") {{
+    location.href = url;
+    return;
+  }}
+  if (v.length == 16) {{
+    v += ":00";
+  }}
+  // The offset is resolved by the server for the input date-time itself (see `parse_datetime_param`).
+  location.href = url + "&" + param + "=" + encodeURIComponent(v + "[{}]");
+}}
+function set_time_zone(v) {{
+  if (v == "browser") {{
+    // The IANA name (not the current offset) keeps the DST changes of the browser's time zone.
+    v = Intl.DateTimeFormat().resolvedOptions().timeZone;
+  }}
+  document.cookie = "{}=" + v + "; path=/; max-age=31536000";
+  // The `tz` query parameter takes precedence over the cookie.
+  var url = new URL(location.href);
+  url.searchParams.delete("tz");
+  location.href = url.toString();
+}}
+</script>
+"#,
+        js_string(&url(None, end)),
+        js_string(&url(start, None)),
+        tz,
+        TIME_ZONE_COOKIE
+    );
+    md
+}
+
+fn input_value(tz: TimeZone, t: Option<DateTime>) -> String {
+    if let Some(t) = t {
+        format!(
+            "value={:?}",
+            tz.convert(t).format("%Y-%m-%dT%H:%M:%S").to_string()
+        )
+    } else {
+        "".to_owned()
+    }
+}
+
+fn js_string(s: &str) -> String {
+    serde_json::to_string(s)
+        .expect("unreachable")
+        .replace("</", "<\\/")
+}
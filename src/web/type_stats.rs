@@ -0,0 +1,250 @@
+//! Usage statistics of artifact and execution types.
+use crate::graph::{BatchStore, NodeId};
+use crate::mlmd::event::EventType;
+use crate::mlmd::property::PropertyValue;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Maximum number of the latest instances that the statistics are computed from.
+pub const SAMPLE_LIMIT: usize = 10000;
+
+/// Maximum number of the latest instances whose events are counted.
+pub const EVENT_SAMPLE_LIMIT: usize = 1000;
+
+/// Number of the latest instances shown on a type page.
+pub const RECENT_LIMIT: usize = 10;
+
+/// Number of the most frequent string values shown per property.
+const TOP_K: usize = 5;
+
+/// Maximum number of characters of a string value shown in the statistics.
+const VALUE_LENGTH_LIMIT: usize = 40;
+
+#[derive(Debug, Default)]
+pub struct TypeStats {
+    samples: usize,
+    properties: BTreeMap<(bool, String), PropertyStats>,
+}
+
+impl TypeStats {
+    /// Makes the statistics of a type that declares `properties`.
+    pub fn new<'a>(properties: impl Iterator<Item = &'a String>) -> Self {
+        Self {
+            samples: 0,
+            properties: properties
+                .map(|k| ((false, k.clone()), PropertyStats::default()))
+                .collect(),
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn add(
+        &mut self,
+        properties: &BTreeMap<String, PropertyValue>,
+        custom_properties: &BTreeMap<String, PropertyValue>,
+    ) {
+        self.samples += 1;
+        for (custom, properties) in [(false, properties), (true, custom_properties)] {
+            for (k, v) in properties {
+                self.properties
+                    .entry((custom, k.clone()))
+                    .or_default()
+                    .add(v);
+            }
+        }
+    }
+
+    /// Makes the markdown table of the fill rates and value distributions of the properties.
+    pub fn properties_md(&self) -> String {
+        if self.properties.is_empty() {
+            return "No properties.\n".to_string();
+        }
+
+        let mut md = String::new();
+        md += "| property | kind | value type | filled | min | max | mean | top values |\n";
+        md += "|----------|------|------------|--------|-----|-----|------|------------|\n";
+        for ((custom, name), stats) in &self.properties {
+            let (min, max, mean) = if let Some(x) = &stats.numeric {
+                (
+                    x.min.to_string(),
+                    x.max.to_string(),
+                    format!("{:.4}", x.sum / x.count as f64),
+                )
+            } else {
+                Default::default()
+            };
+            md += &format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                name,
+                if *custom { "custom" } else { "declared" },
+                stats
+                    .value_types
+                    .iter()
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                fill_rate(stats.filled, self.samples),
+                min,
+                max,
+                mean,
+                stats.top_values_md()
+            );
+        }
+        md
+    }
+}
+
+#[derive(Debug, Default)]
+struct PropertyStats {
+    filled: usize,
+    value_types: BTreeSet<&'static str>,
+    numeric: Option<NumericStats>,
+    strings: HashMap<String, usize>,
+}
+
+impl PropertyStats {
+    fn add(&mut self, value: &PropertyValue) {
+        let mut value = value;
+        while let PropertyValue::Link { value: x, .. } = value {
+            value = x;
+        }
+
+        self.filled += 1;
+        match value {
+            PropertyValue::Int(x) => {
+                self.value_types.insert("INT");
+                self.add_numeric(f64::from(*x));
+            }
+            PropertyValue::Double(x) => {
+                self.value_types.insert("DOUBLE");
+                self.add_numeric(*x);
+            }
+            PropertyValue::String(x) => {
+                self.value_types.insert("STRING");
+                *self.strings.entry(x.clone()).or_default() += 1;
+            }
+            PropertyValue::Link { .. } => unreachable!(),
+        }
+    }
+
+    fn add_numeric(&mut self, x: f64) {
+        if let Some(stats) = &mut self.numeric {
+            stats.min = stats.min.min(x);
+            stats.max = stats.max.max(x);
+            stats.sum += x;
+            stats.count += 1;
+        } else {
+            self.numeric = Some(NumericStats {
+                min: x,
+                max: x,
+                sum: x,
+                count: 1,
+            });
+        }
+    }
+
+    fn top_values_md(&self) -> String {
+        let mut values = self.strings.iter().collect::<Vec<_>>();
+        values.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let mut md = values
+            .iter()
+            .take(TOP_K)
+            .map(|(v, n)| format!("`{}` ({})", escape_value(v), n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if values.len() > TOP_K {
+            md += &format!(", ... ({} distinct)", values.len());
+        }
+        md
+    }
+}
+
+#[derive(Debug)]
+struct NumericStats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: usize,
+}
+
+/// Numbers of the events between some nodes and the nodes of a neighbouring type.
+///
+/// The direction is from the perspective of the executions on either side.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NeighborCounts {
+    /// Number of the output events (an execution produced an artifact).
+    pub produced: usize,
+    /// Number of the input events (an execution consumed an artifact).
+    pub consumed: usize,
+}
+
+/// Counts the types of the nodes connected to `nodes` by events.
+///
+/// The result maps each type name to the numbers of the events to / from the nodes of the type.
+pub async fn count_neighbor_types(
+    store: &mut mlmd::MetadataStore,
+    nodes: &[NodeId],
+) -> anyhow::Result<BTreeMap<String, NeighborCounts>> {
+    let mut store = BatchStore::new(store);
+    let mut neighbors = Vec::new();
+    for (id, event) in store.get_events(nodes).await? {
+        let neighbor = match id {
+            NodeId::Artifact(_) => NodeId::Execution(event.execution_id.get()),
+            NodeId::Execution(_) => NodeId::Artifact(event.artifact_id.get()),
+        };
+        neighbors.push((neighbor, EventType::from(event.ty)));
+    }
+
+    let ids = neighbors
+        .iter()
+        .map(|x| x.0)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let type_names = store
+        .get_nodes(&ids)
+        .await?
+        .into_iter()
+        .map(|x| (x.id(), x.type_name().to_owned()))
+        .collect::<HashMap<_, _>>();
+
+    let mut counts: BTreeMap<String, NeighborCounts> = BTreeMap::new();
+    for (id, ty) in neighbors {
+        let type_name = if let Some(x) = type_names.get(&id) {
+            x
+        } else {
+            continue;
+        };
+        let count = counts.entry(type_name.clone()).or_default();
+        if ty.is_input() {
+            count.consumed += 1;
+        } else if ty.is_output() {
+            count.produced += 1;
+        }
+    }
+    Ok(counts)
+}
+
+fn fill_rate(filled: usize, samples: usize) -> String {
+    if samples == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{:.1}% ({})",
+        filled as f64 * 100.0 / samples as f64,
+        filled
+    )
+}
+
+fn escape_value(s: &str) -> String {
+    let s = if s.chars().count() <= VALUE_LENGTH_LIMIT {
+        s.to_owned()
+    } else {
+        let mut s = s.chars().take(VALUE_LENGTH_LIMIT - 1).collect::<String>();
+        s.push('…');
+        s
+    };
+    s.replace('|', "\\|").replace('`', "'").replace('\n', " ")
+}
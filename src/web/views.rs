@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Named URL of a plot or list page.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct View {
+    pub id: u32,
+    pub name: String,
+    pub url: String,
+}
+
+impl View {
+    /// Returns the short permalink of the view.
+    pub fn permalink(&self) -> String {
+        format!("/v/{}", self.id)
+    }
+}
+
+/// Saved views persisted in a JSON file.
+///
+/// If no file is specified, the views are only kept in memory.
+#[derive(Debug)]
+pub struct ViewStore {
+    path: Option<PathBuf>,
+    file: Mutex<ViewsFile>,
+}
+
+/// Content of the views file.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ViewsFile {
+    /// ID of the next view.
+    ///
+    /// IDs of removed views aren't reused so that shared permalinks never point to other views.
+    next_id: u32,
+    views: Vec<View>,
+}
+
+impl ViewStore {
+    pub fn open(path: Option<PathBuf>) -> anyhow::Result<Self> {
+        let file = match &path {
+            Some(path) if path.exists() => {
+                serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))?
+            }
+            _ => ViewsFile {
+                next_id: 1,
+                views: Vec::new(),
+            },
+        };
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn list(&self) -> Vec<View> {
+        self.file.lock().expect("poisoned").views.clone()
+    }
+
+    pub fn get(&self, id: u32) -> Option<View> {
+        self.file
+            .lock()
+            .expect("poisoned")
+            .views
+            .iter()
+            .find(|v| v.id == id)
+            .cloned()
+    }
+
+    pub fn add(&self, name: &str, url: &str) -> anyhow::Result<View> {
+        anyhow::ensure!(!name.trim().is_empty(), "empty view name");
+        anyhow::ensure!(is_weih_url(url), "not a weih URL: {:?}", url);
+
+        let mut file = self.file.lock().expect("poisoned");
+        let view = View {
+            id: file.next_id,
+            name: name.trim().to_owned(),
+            url: url.to_owned(),
+        };
+        file.next_id += 1;
+        file.views.push(view.clone());
+        self.save(&file)?;
+        Ok(view)
+    }
+
+    pub fn remove(&self, id: u32) -> anyhow::Result<Option<View>> {
+        let mut file = self.file.lock().expect("poisoned");
+        let i = if let Some(i) = file.views.iter().position(|v| v.id == id) {
+            i
+        } else {
+            return Ok(None);
+        };
+        let view = file.views.remove(i);
+        self.save(&file)?;
+        Ok(Some(view))
+    }
+
+    fn save(&self, file: &ViewsFile) -> anyhow::Result<()> {
+        let path = if let Some(path) = &self.path {
+            path
+        } else {
+            return Ok(());
+        };
+
+        // Writes to a temporary file first so that a crash doesn't leave a broken file.
+        let tmp_path = path.with_extension("tmp");
+        serde_json::to_writer_pretty(std::fs::File::create(&tmp_path)?, file)?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Returns `true` if `url` is a path on this server (views must not lead to other sites).
+///
+/// Browsers treat `/\` at the beginning of a URL like `//`, so it's rejected as well.
+pub fn is_weih_url(url: &str) -> bool {
+    url.starts_with('/') && !url.starts_with("//") && !url.starts_with("/\\")
+}
+
+/// Makes the markdown of the link to save the page at `req` as a view.
+pub fn save_link_md(req: &actix_web::HttpRequest) -> String {
+    format!(
+        "[Save view](/views/new?{})",
+        crate::web::query::to_qs(&[("url", req.uri().to_string())])
+    )
+}